@@ -0,0 +1,51 @@
+use std::fmt::Display;
+
+use bevy::prelude::{Bundle, Component};
+
+use super::{
+    common::{Name, ID},
+    spatial::SpatialBundle,
+};
+
+#[derive(Component, Default)]
+pub struct StarCluster;
+
+/// Cluster centre is the bundle's `Position`.
+#[derive(Bundle)]
+pub struct StarClusterBundle {
+    pub id: ID,
+    pub name: Name,
+    pub cluster_type: CClusterType,
+    pub radius: ClusterRadius,
+    pub spatial: SpatialBundle,
+    pub cluster: StarCluster,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ClusterType {
+    /// Young cluster in the arms.
+    Open,
+    /// Old cluster in the halo.
+    Globular,
+}
+
+impl Display for ClusterType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::Open => "open cluster",
+            Self::Globular => "globular cluster",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct CClusterType(pub ClusterType);
+
+/// Plummer scale radius.
+#[derive(Component, Clone, Copy)]
+pub struct ClusterRadius(pub f32);
+
+/// ID of the cluster a star belongs to.
+#[derive(Component, Clone)]
+pub struct ClusterMembership(pub ID);
@@ -1,5 +1,8 @@
 use bevy::prelude::{Component, Entity};
-use uuid::Uuid;
+use rand::Rng;
+use uuid::{Builder, Uuid};
+
+use super::star::{SystemCompanion, SystemStar};
 
 #[derive(Component, Default)]
 pub struct Name(pub String);
@@ -13,10 +16,23 @@ pub struct Radius(pub f32);
 #[derive(Component, Default, Clone, Copy)]
 pub struct Mass(pub f32);
 
+/// Star whose planets are made once first asked for, around the stars as
+/// they were when the galaxy was made so the result does not depend on when.
+/// What happened to the stars since is applied on top.
 #[derive(Component)]
-pub struct PendingGeneration;
+pub struct PendingGeneration {
+    pub star: SystemStar,
+    /// Sorted by separation.
+    pub companions: Vec<SystemCompanion>,
+    /// Stars of the system that went supernova in the meantime.
+    pub exploded: Vec<ID>,
+}
+
+/// Seed of the star's planetary system, derived from the galaxy seed.
+#[derive(Component, Default, Clone, Copy)]
+pub struct SystemSeed(pub u64);
 
-#[derive(Component, Clone, PartialEq)]
+#[derive(Component, Clone, PartialEq, Eq, Hash)]
 pub struct ID(pub String);
 
 impl Default for ID {
@@ -24,3 +40,15 @@ impl Default for ID {
         Self(Uuid::new_v4().to_string())
     }
 }
+
+impl ID {
+    /// Random (v4) ID drawn from the given generator, so seeded generation
+    /// reproduces the same IDs.
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
+        Self(
+            Builder::from_random_bytes(rng.random())
+                .into_uuid()
+                .to_string(),
+        )
+    }
+}
@@ -1,28 +1,74 @@
 use bevy::prelude::Resource;
-use egui::Vec2;
+use egui::{pos2, Pos2, Vec2};
 use std::sync::{Arc, Mutex};
-use vecmath::Vector2;
+use vecmath::Vector3;
+
+use crate::game::resourses::sectors::Sector;
 
 use super::{
+    cluster::ClusterType,
     common::ID,
-    star::{StarClass, StarType},
+    nebula::{NebulaShape, NebulaType},
+    star::{EvolutionStage, FlareActivity, SpectralDesignation, StarClass, StarType, Variability},
 };
 
 #[derive(Resource)]
 pub struct GalaxyMap {
     pub stars: Arc<Mutex<Vec<StarData>>>,
+    pub core: Option<CoreData>,
+    pub nebulae: Vec<NebulaData>,
+    pub clusters: Vec<ClusterData>,
+    pub sectors: Vec<Sector>,
     pub position: Vec2,
     pub zoom: f32,
     pub hovered: Option<usize>,
+    pub search: String,
+    /// Galaxy position to centre the view on next frame.
+    pub focus: Option<Vector3<f32>>,
+    pub projection: MapProjection,
+    /// Start of the route drawn to the hovered star.
+    pub route_origin: Option<ID>,
 }
 
 impl Default for GalaxyMap {
     fn default() -> Self {
         Self {
             stars: Arc::<Mutex<Vec<StarData>>>::default(),
+            core: None,
+            nebulae: Vec::new(),
+            clusters: Vec::new(),
+            sectors: Vec::new(),
             position: Vec2::default(),
             zoom: 1.0,
             hovered: None,
+            search: String::new(),
+            focus: None,
+            projection: MapProjection::default(),
+            route_origin: None,
+        }
+    }
+}
+
+/// How galactic positions are flattened onto the map.
+#[derive(Clone, Copy, PartialEq, Default)]
+pub enum MapProjection {
+    #[default]
+    TopDown,
+    /// Disk tipped away from the viewer by `angle` radians.
+    Tilted { angle: f32 },
+}
+
+impl MapProjection {
+    pub const DEFAULT_TILT: f32 = 1.0;
+
+    /// Map coordinates of a galactic position, with height drawn upwards.
+    pub fn project(&self, position: Vector3<f32>) -> Pos2 {
+        match *self {
+            Self::TopDown => pos2(position[0], position[1]),
+            Self::Tilted { angle } => pos2(
+                position[0],
+                position[1] * angle.cos() - position[2] * angle.sin(),
+            ),
         }
     }
 }
@@ -30,8 +76,85 @@ impl Default for GalaxyMap {
 #[derive(Clone)]
 pub struct StarData {
     pub id: ID,
-    pub position: Vector2<f32>,
+    pub name: String,
+    pub designation: String,
+    pub position: Vector3<f32>,
     pub star_type: StarType,
+    pub subclass: f32,
     pub star_class: StarClass,
+    pub metallicity: f32,
+    /// In kelvins.
+    pub temperature: f32,
+    /// In solar luminosities, without `output`.
+    pub luminosity: f32,
+    /// In years.
+    pub age: f64,
+    pub stage: EvolutionStage,
+    pub variability: Variability,
+    pub flares: FlareActivity,
+    /// Current luminosity multiplier, see `LuminosityModifier`.
+    pub output: f32,
+    pub flaring: bool,
+    /// Name of the star cluster the star belongs to.
+    pub cluster: Option<String>,
+    pub sector: String,
+    pub companions: Vec<CompanionData>,
     // pub discovered: bool,
 }
+
+/// Companion star of a multiple system.
+#[derive(Clone)]
+pub struct CompanionData {
+    pub name: String,
+    pub star_type: StarType,
+    pub subclass: f32,
+    pub star_class: StarClass,
+    /// In kelvins.
+    pub temperature: f32,
+    /// In solar luminosities.
+    pub luminosity: f32,
+    /// From the star it orbits, in AU.
+    pub separation: f32,
+}
+
+impl StarData {
+    pub fn spectral(&self) -> SpectralDesignation {
+        SpectralDesignation {
+            star_type: self.star_type,
+            subclass: self.subclass,
+            star_class: self.star_class,
+        }
+    }
+}
+
+impl CompanionData {
+    pub fn spectral(&self) -> SpectralDesignation {
+        SpectralDesignation {
+            star_type: self.star_type,
+            subclass: self.subclass,
+            star_class: self.star_class,
+        }
+    }
+}
+
+/// Supermassive black hole at the centre of the galaxy.
+#[derive(Clone)]
+pub struct CoreData {
+    pub name: String,
+    pub position: Vector3<f32>,
+}
+
+#[derive(Clone)]
+pub struct NebulaData {
+    pub name: String,
+    pub nebula_type: NebulaType,
+    pub shape: NebulaShape,
+}
+
+#[derive(Clone)]
+pub struct ClusterData {
+    pub name: String,
+    pub cluster_type: ClusterType,
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
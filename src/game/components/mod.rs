@@ -1,7 +1,10 @@
+pub mod cluster;
 pub mod common;
 pub mod galaxy_map;
+pub mod nebula;
 pub mod orbit;
 pub mod planet;
 pub mod spatial;
 pub mod star;
+pub mod structure;
 pub mod system_map;
@@ -0,0 +1,54 @@
+use std::fmt::Display;
+
+use bevy::prelude::{Bundle, Component};
+use vecmath::{vec3_len, vec3_sub, Vector3};
+
+use super::common::{Name, ID};
+
+#[derive(Component, Default)]
+pub struct Nebula;
+
+#[derive(Bundle)]
+pub struct NebulaBundle {
+    pub id: ID,
+    pub name: Name,
+    pub nebula_type: CNebulaType,
+    pub shape: NebulaShape,
+    pub nebula: Nebula,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum NebulaType {
+    Emission,
+    Dark,
+    Reflection,
+}
+
+impl Display for NebulaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Self::Emission => "emission nebula",
+            Self::Dark => "dark nebula",
+            Self::Reflection => "reflection nebula",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub struct CNebulaType(pub NebulaType);
+
+/// Cloud volume as a union of overlapping spherical puffs.
+#[derive(Component, Clone)]
+pub struct NebulaShape {
+    /// Puff centres and radii.
+    pub puffs: Vec<(Vector3<f32>, f32)>,
+}
+
+impl NebulaShape {
+    pub fn contains(&self, point: Vector3<f32>) -> bool {
+        self.puffs
+            .iter()
+            .any(|(center, radius)| vec3_len(vec3_sub(point, *center)) <= *radius)
+    }
+}
@@ -1,24 +1,114 @@
-use bevy::prelude::{Bundle, Component, Entity};
+use std::f64::consts::{PI, TAU};
+
+use bevy::prelude::{Bundle, Component};
+use vecmath::Vector3;
 
 use super::common::ID;
 
+/// Meters per astronomical unit.
+pub static AU: f64 = 149597870700.0;
+
+/// Kilograms per solar mass.
+pub static SOLAR_MASS: f64 = 1.989e30;
+
 #[derive(Bundle, Clone)]
 pub struct OrbitBundle {
-    pub orbit_radius: OrbitRadius,
+    pub elements: OrbitalElements,
     pub orbit_period: OrbitPeriod,
-    pub orbit_position: OrbitPosition,
 
     pub parent: Parent,
 }
 
-#[derive(Component, Clone)]
-pub struct OrbitRadius(pub f64);
+/// Keplerian elements of an orbit around the parent. Angles are in radians,
+/// inclination and node against the system's reference plane.
+#[derive(Component, Clone, Copy, Default)]
+pub struct OrbitalElements {
+    /// In meters.
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub argument_of_periapsis: f64,
+    pub longitude_of_ascending_node: f64,
+    /// At game time zero.
+    pub mean_anomaly: f64,
+}
+
+impl OrbitalElements {
+    pub fn circular(semi_major_axis: f64, mean_anomaly: f64) -> Self {
+        Self {
+            semi_major_axis,
+            mean_anomaly,
+            ..Default::default()
+        }
+    }
+
+    /// In meters.
+    pub fn periapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 - self.eccentricity)
+    }
+
+    /// In meters.
+    pub fn apoapsis(&self) -> f64 {
+        self.semi_major_axis * (1.0 + self.eccentricity)
+    }
+
+    /// Offset from the parent in meters after `years`, for an orbit taking
+    /// `period` years.
+    pub fn position_at(&self, years: f64, period: f64) -> Vector3<f64> {
+        let mean_anomaly = if period > 0.0 {
+            self.mean_anomaly + TAU * (years / period).fract()
+        } else {
+            self.mean_anomaly
+        };
+        self.point_at(self.eccentric_anomaly(mean_anomaly))
+    }
+
+    /// Point of the orbit at the given eccentric anomaly, in meters from the
+    /// parent.
+    pub fn point_at(&self, eccentric_anomaly: f64) -> Vector3<f64> {
+        let e = self.eccentricity;
+        let x = self.semi_major_axis * (eccentric_anomaly.cos() - e);
+        let y = self.semi_major_axis * (1.0 - e * e).sqrt() * eccentric_anomaly.sin();
+
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        let (sin_n, cos_n) = self.longitude_of_ascending_node.sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        [
+            x * (cos_n * cos_w - sin_n * sin_w * cos_i)
+                - y * (cos_n * sin_w + sin_n * cos_w * cos_i),
+            x * (sin_n * cos_w + cos_n * sin_w * cos_i)
+                + y * (cos_n * cos_w * cos_i - sin_n * sin_w),
+            x * sin_w * sin_i + y * cos_w * sin_i,
+        ]
+    }
+
+    /// Solves Kepler's equation `M = E - e sin E` by Newton's method.
+    fn eccentric_anomaly(&self, mean_anomaly: f64) -> f64 {
+        let e = self.eccentricity;
+        let mean_anomaly = mean_anomaly.rem_euclid(TAU);
+        let mut anomaly = if e < 0.8 { mean_anomaly } else { PI };
+        for _ in 0..16 {
+            let step = (anomaly - e * anomaly.sin() - mean_anomaly) / (1.0 - e * anomaly.cos());
+            anomaly -= step;
+            if step.abs() < 1e-10 {
+                break;
+            }
+        }
+        anomaly
+    }
+}
 
 #[derive(Component, Clone)]
 pub struct Parent(pub Option<ID>);
 
+/// In years.
 #[derive(Component, Clone)]
 pub struct OrbitPeriod(pub f64);
 
-#[derive(Component, Clone)]
-pub struct OrbitPosition(pub f64);
+impl OrbitPeriod {
+    /// Kepler's third law for an orbit of `semi_major_axis` meters around a
+    /// total of `mass` solar masses.
+    pub fn kepler(semi_major_axis: f64, mass: f64) -> Self {
+        Self(((semi_major_axis / AU).powi(3) / mass).sqrt())
+    }
+}
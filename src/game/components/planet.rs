@@ -1,8 +1,10 @@
+use std::fmt::Display;
+
 use bevy::prelude::{Bundle, Component};
 
 use super::{
     common::{Mass, Radius, ID},
-    orbit::OrbitBundle,
+    orbit::{OrbitBundle, AU},
 };
 
 #[derive(Bundle, Clone)]
@@ -12,13 +14,296 @@ pub struct CelestialBodyBundle {
     pub mass: Mass,
     pub radius: Radius,
     pub body_type: BodyType,
+    pub heavy_elements: HeavyElements,
+    pub equilibrium_temperature: EquilibriumTemperature,
+    pub water: WaterFraction,
+    pub annulus: Annulus,
     pub orbit_bundle: OrbitBundle,
+    pub environment: EnvironmentBundle,
 }
 
 impl CelestialBodyBundle {
     pub fn density(&self) -> f32 {
         self.mass.0 / self.radius.0.powi(3) / std::f32::consts::PI * 4.0 / 3.0
     }
+
+    /// Closest and furthest the body gets from its parent, in meters.
+    pub fn extent(&self) -> (f64, f64) {
+        if self.annulus.is_band() {
+            (self.annulus.inner, self.annulus.outer)
+        } else {
+            let elements = &self.orbit_bundle.elements;
+            (elements.periapsis(), elements.apoapsis())
+        }
+    }
+}
+
+/// Kilograms per Earth mass.
+pub static EARTH_MASS: f32 = 5.976e24;
+
+/// Lightest gas giant in kg, a tenth of Jupiter's mass.
+pub static GAS_GIANT_MASS: f32 = 0.1 * 1.898e27;
+
+/// In m³ kg⁻¹ s⁻².
+static GRAVITATIONAL_CONSTANT: f64 = 6.674e-11;
+
+/// In m/s².
+static EARTH_GRAVITY: f64 = 9.81;
+
+/// In J mol⁻¹ K⁻¹.
+static GAS_CONSTANT: f64 = 8.314;
+
+/// Radius in km of a body of `mass` kg, from the mass-radius relation of
+/// Chen & Kipping (2017): rocky up to 2 Earth masses, Neptunian up to 130,
+/// then giants that barely grow with mass.
+pub fn planet_radius(mass: f32) -> f32 {
+    let mass = mass / EARTH_MASS;
+    let earth_radii = if mass < 2.0 {
+        mass.powf(0.279)
+    } else if mass < 130.0 {
+        0.808 * mass.powf(0.589)
+    } else {
+        14.2 * (mass / 130.0).powf(-0.044)
+    };
+    earth_radii * 6371.0
+}
+
+/// Water mass fraction the body formed with.
+#[derive(Component, Clone, Copy, Default)]
+pub struct WaterFraction(pub f32);
+
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum PlanetClass {
+    #[default]
+    Rocky,
+    SuperEarth,
+    Ocean,
+    Ice,
+    Lava,
+    MiniNeptune,
+    IceGiant,
+    GasGiant,
+}
+
+impl Display for PlanetClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Rocky => write!(f, "Rocky"),
+            Self::SuperEarth => write!(f, "Super-Earth"),
+            Self::Ocean => write!(f, "Ocean"),
+            Self::Ice => write!(f, "Ice"),
+            Self::Lava => write!(f, "Lava"),
+            Self::MiniNeptune => write!(f, "Mini-Neptune"),
+            Self::IceGiant => write!(f, "Ice giant"),
+            Self::GasGiant => write!(f, "Gas giant"),
+        }
+    }
+}
+
+impl PlanetClass {
+    /// Class of a body of `mass` Earth masses, formed with `water`, at an
+    /// equilibrium temperature of `temperature` kelvins.
+    pub fn classify(mass: f32, water: f32, temperature: f32) -> Self {
+        if mass * EARTH_MASS > GAS_GIANT_MASS {
+            Self::GasGiant
+        } else if mass >= 10.0 {
+            Self::IceGiant
+        } else if temperature > 1000.0 {
+            Self::Lava
+        } else if mass >= 5.0 {
+            Self::MiniNeptune
+        } else if water > 0.05 && temperature < 150.0 {
+            Self::Ice
+        } else if water > 0.05 {
+            Self::Ocean
+        } else if mass >= 2.0 {
+            Self::SuperEarth
+        } else {
+            Self::Rocky
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Default)]
+pub struct CPlanetClass(pub PlanetClass);
+
+/// In multiples of Earth's.
+#[derive(Component, Clone, Copy, Default)]
+pub struct SurfaceGravity(pub f32);
+
+/// In km/s.
+#[derive(Component, Clone, Copy, Default)]
+pub struct EscapeVelocity(pub f32);
+
+/// Main gases of an atmosphere.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum AtmosphereComposition {
+    #[default]
+    None,
+    RockVapour,
+    CarbonDioxide,
+    Nitrogen,
+    NitrogenMethane,
+    Steam,
+    HydrogenHelium,
+}
+
+impl Display for AtmosphereComposition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "none"),
+            Self::RockVapour => write!(f, "rock vapour"),
+            Self::CarbonDioxide => write!(f, "carbon dioxide"),
+            Self::Nitrogen => write!(f, "nitrogen"),
+            Self::NitrogenMethane => write!(f, "nitrogen and methane"),
+            Self::Steam => write!(f, "steam"),
+            Self::HydrogenHelium => write!(f, "hydrogen and helium"),
+        }
+    }
+}
+
+impl AtmosphereComposition {
+    /// Greenhouse optical depth per bar, fitted to Earth, Venus and Titan.
+    pub fn opacity(&self) -> f32 {
+        match self {
+            Self::Nitrogen => 0.84,
+            Self::CarbonDioxide => 1.47,
+            Self::NitrogenMethane => 0.66,
+            Self::Steam => 2.0,
+            Self::None | Self::RockVapour | Self::HydrogenHelium => 0.0,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, Default)]
+pub struct Atmosphere {
+    /// At the surface, in bar. Infinite for envelopes with no surface.
+    pub pressure: f32,
+    pub composition: AtmosphereComposition,
+}
+
+impl Atmosphere {
+    /// Gas held over billions of years by a body of `mass` Earth masses
+    /// escaping at `escape_velocity` km/s, at `temperature` kelvins. Surface
+    /// pressure grows as mass to the 2.2, Earth to Mars.
+    fn around(class: PlanetClass, mass: f32, escape_velocity: f32, temperature: f32) -> Self {
+        let atmosphere = |pressure, composition| Self {
+            pressure,
+            composition,
+        };
+        // Jeans escape is slow enough once escape velocity is six times the
+        // gas's thermal speed. `molar_mass` is in kg/mol.
+        let kept = |molar_mass: f64| {
+            let thermal = (3.0 * GAS_CONSTANT * temperature as f64 / molar_mass).sqrt();
+            escape_velocity as f64 * 1000.0 > 6.0 * thermal
+        };
+        let pressure = mass.powf(2.2);
+
+        match class {
+            PlanetClass::GasGiant | PlanetClass::IceGiant | PlanetClass::MiniNeptune => {
+                atmosphere(f32::INFINITY, AtmosphereComposition::HydrogenHelium)
+            }
+            // A thin film of silicates boiled off the magma.
+            PlanetClass::Lava => atmosphere(1e-4, AtmosphereComposition::RockVapour),
+            PlanetClass::Ocean if temperature > 400.0 && kept(0.018) => {
+                atmosphere(100.0 * pressure, AtmosphereComposition::Steam)
+            }
+            PlanetClass::Ice if kept(0.028) => {
+                atmosphere(pressure, AtmosphereComposition::NitrogenMethane)
+            }
+            // Runaway greenhouse, as on Venus.
+            _ if temperature > 320.0 && mass >= 0.3 && kept(0.044) => {
+                atmosphere(90.0 * pressure, AtmosphereComposition::CarbonDioxide)
+            }
+            _ if mass >= 0.5 && kept(0.028) => {
+                atmosphere(pressure, AtmosphereComposition::Nitrogen)
+            }
+            _ if kept(0.044) => atmosphere(pressure, AtmosphereComposition::CarbonDioxide),
+            _ => atmosphere(0.0, AtmosphereComposition::None),
+        }
+    }
+
+    /// Greenhouse optical depth.
+    pub fn optical_depth(&self) -> f32 {
+        let opacity = self.composition.opacity();
+        // Giant envelopes are infinitely deep but add no greenhouse here.
+        if opacity > 0.0 {
+            opacity * self.pressure
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Bond albedo.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Albedo(pub f32);
+
+/// Mean, in kelvins.
+#[derive(Component, Clone, Copy, Default)]
+pub struct SurfaceTemperature(pub f32);
+
+/// What a body is like on and above its surface.
+#[derive(Bundle, Clone, Copy, Default)]
+pub struct EnvironmentBundle {
+    pub class: CPlanetClass,
+    pub gravity: SurfaceGravity,
+    pub escape_velocity: EscapeVelocity,
+    pub atmosphere: Atmosphere,
+    pub albedo: Albedo,
+    pub surface_temperature: SurfaceTemperature,
+}
+
+impl EnvironmentBundle {
+    /// Environment of a body of `mass` kg and `radius` km, formed with
+    /// `water`, at an equilibrium temperature of `temperature` kelvins.
+    pub fn derive(mass: f32, radius: f32, water: f32, temperature: f32) -> Self {
+        let gm = GRAVITATIONAL_CONSTANT * mass as f64;
+        let radius = radius as f64 * 1000.0;
+        let gravity = gm / radius.powi(2) / EARTH_GRAVITY;
+        let escape_velocity = ((2.0 * gm / radius).sqrt() / 1000.0) as f32;
+
+        let earth_masses = mass / EARTH_MASS;
+        let class = PlanetClass::classify(earth_masses, water, temperature);
+        let atmosphere = Atmosphere::around(class, earth_masses, escape_velocity, temperature);
+        let albedo: f32 = match (class, atmosphere.composition) {
+            (PlanetClass::GasGiant, _) => 0.34,
+            (PlanetClass::IceGiant | PlanetClass::MiniNeptune, _) => 0.3,
+            (PlanetClass::Ice, _) => 0.6,
+            (PlanetClass::Lava, _) => 0.1,
+            (_, AtmosphereComposition::Steam) => 0.75,
+            (_, AtmosphereComposition::CarbonDioxide) if atmosphere.pressure > 10.0 => 0.75,
+            (PlanetClass::Ocean, _) | (_, AtmosphereComposition::Nitrogen) => 0.3,
+            _ => 0.12,
+        };
+        // Grey-atmosphere greenhouse on top of the light actually absorbed.
+        let surface_temperature = temperature
+            * (1.0 - albedo).powf(0.25)
+            * (1.0 + 0.75 * atmosphere.optical_depth()).powf(0.25);
+
+        Self {
+            class: CPlanetClass(class),
+            gravity: SurfaceGravity(gravity as f32),
+            escape_velocity: EscapeVelocity(escape_velocity),
+            atmosphere,
+            albedo: Albedo(albedo),
+            surface_temperature: SurfaceTemperature(surface_temperature),
+        }
+    }
+}
+
+/// Radial extent of a belt or ring, in meters from its parent. Both edges
+/// are zero for bodies that are a single point.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Annulus {
+    pub inner: f64,
+    pub outer: f64,
+}
+
+impl Annulus {
+    pub fn is_band(&self) -> bool {
+        self.outer > 0.0
+    }
 }
 
 #[derive(Component, Clone)]
@@ -27,6 +312,29 @@ pub struct SystemId(pub ID);
 #[derive(Component, Clone, Copy)]
 pub struct BodyType(pub BodyTypes);
 
+/// Heavy-element deposit richness, `1.0` is a rocky body around a
+/// solar-metallicity star.
+#[derive(Component, Clone, Copy, Default)]
+pub struct HeavyElements(pub f32);
+
+/// Temperature a black body would settle at under the starlight the body
+/// receives, in kelvins.
+#[derive(Component, Clone, Copy, Default)]
+pub struct EquilibriumTemperature(pub f32);
+
+impl EquilibriumTemperature {
+    /// `insolation` is in multiples of what Earth receives.
+    pub fn from_insolation(insolation: f64) -> Self {
+        Self(278.6 * insolation.powf(0.25) as f32)
+    }
+}
+
+/// Starlight from `luminosity` solar luminosities at `distance` meters, in
+/// multiples of what Earth receives.
+pub fn insolation(luminosity: f32, distance: f64) -> f64 {
+    luminosity as f64 / (distance / AU).powi(2)
+}
+
 #[derive(Clone, Copy)]
 pub enum BodyTypes {
     Planet,
@@ -34,4 +342,19 @@ pub enum BodyTypes {
     Moon,
     AsteroidBelt,
     Ring,
+    /// Companion star of a multiple system.
+    Star,
+}
+
+impl Display for BodyTypes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Planet => write!(f, "Planet"),
+            Self::GasGiant => write!(f, "Gas giant"),
+            Self::Moon => write!(f, "Moon"),
+            Self::AsteroidBelt => write!(f, "Asteroid belt"),
+            Self::Ring => write!(f, "Ring"),
+            Self::Star => write!(f, "Star"),
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use bevy::prelude::{Bundle, Component};
-use vecmath::Vector2;
+use vecmath::{Vector2, Vector3};
 
 #[derive(Bundle, Default)]
 pub struct SpatialBundle {
@@ -8,20 +8,26 @@ pub struct SpatialBundle {
     pub acceleration: Acceleration,
 }
 
+/// Galactic coordinates, with `z` measured from the plane of the disk.
 #[derive(Component, Default)]
-pub struct Position(pub Vector2<f32>);
+pub struct Position(pub Vector3<f32>);
 
 #[derive(Component, Default)]
-pub struct Velocity(pub Vector2<f32>);
+pub struct Velocity(pub Vector3<f32>);
 
 #[derive(Component, Default)]
-pub struct Acceleration(pub Vector2<f32>);
+pub struct Acceleration(pub Vector3<f32>);
 
 impl SpatialBundle {
-    pub fn from_position(pos: Vector2<f32>) -> Self {
+    pub fn from_position(pos: Vector3<f32>) -> Self {
         Self {
             position: Position(pos),
             ..Default::default()
         }
     }
 }
+
+/// Projection of a galactic position onto the plane of the disk.
+pub fn planar(position: Vector3<f32>) -> Vector2<f32> {
+    [position[0], position[1]]
+}
@@ -1,9 +1,15 @@
-use std::{default, fmt::Display, ops::RangeInclusive};
+use std::{default, fmt::Display, ops::RangeInclusive, str::FromStr};
 
 use bevy::prelude::{default, Bundle, Component};
-use rand::{rng, thread_rng, Rng};
+use rand::Rng;
+use rand_distr::{LogNormal, StandardNormal};
 
-use super::common::{Mass, Name, Radius, Temperature, ID};
+use super::{
+    common::{Mass, Name, Radius, Temperature, ID},
+    orbit::{OrbitBundle, OrbitPeriod, OrbitalElements, Parent, AU},
+    planet::{BodyType, BodyTypes, SystemId},
+    spatial::SpatialBundle,
+};
 
 #[derive(Component, Default)]
 pub struct Star;
@@ -12,18 +18,264 @@ pub struct Star;
 pub struct StarBundle {
     pub id: ID,
     pub name: Name,
+    pub designation: CatalogueDesignation,
     pub star_type: CStarType,
+    pub subclass: SpectralSubclass,
     pub star_class: CStarClass,
     pub luminosity: Luminosity,
+    pub habitable_zone: HabitableZone,
+    pub frost_line: FrostLine,
+    pub metallicity: Metallicity,
     pub temperature: Temperature,
     pub radius: Radius,
     pub mass: Mass,
+    pub age: Age,
+    pub lifetime: Lifetime,
+    pub stage: CEvolutionStage,
+    pub variability: Variability,
+    pub flares: FlareActivity,
+    pub luminosity_modifier: LuminosityModifier,
     pub star: Star,
 }
 
+/// Star bound to a system's primary. It belongs to the primary's `SystemId`
+/// and orbits the primary, or another companion named by its `Parent`.
+#[derive(Component, Default)]
+pub struct Companion;
+
+#[derive(Bundle)]
+pub struct CompanionBundle {
+    pub id: ID,
+    pub name: Name,
+    pub star_type: CStarType,
+    pub subclass: SpectralSubclass,
+    pub star_class: CStarClass,
+    pub luminosity: Luminosity,
+    pub habitable_zone: HabitableZone,
+    pub frost_line: FrostLine,
+    pub temperature: Temperature,
+    pub radius: Radius,
+    pub mass: Mass,
+    pub age: Age,
+    pub lifetime: Lifetime,
+    pub stage: CEvolutionStage,
+    pub variability: Variability,
+    pub flares: FlareActivity,
+    pub luminosity_modifier: LuminosityModifier,
+    pub system_id: SystemId,
+    pub body_type: BodyType,
+    pub orbit_bundle: OrbitBundle,
+    pub companion: Companion,
+}
+
+/// What a star's planetary system depends on.
+#[derive(Clone)]
+pub struct SystemStar {
+    pub id: ID,
+    pub star_type: StarType,
+    /// In solar masses.
+    pub mass: f32,
+    /// In solar luminosities.
+    pub luminosity: f32,
+    /// [Fe/H] in dex.
+    pub metallicity: f32,
+    /// In meters.
+    pub frost_line: f64,
+}
+
+impl From<&StarBundle> for SystemStar {
+    fn from(star: &StarBundle) -> Self {
+        Self {
+            id: star.id.clone(),
+            star_type: star.star_type.0,
+            mass: star.mass.0,
+            luminosity: star.luminosity.0,
+            metallicity: star.metallicity.0,
+            frost_line: star.frost_line.0,
+        }
+    }
+}
+
+/// Companion of a `SystemStar`.
+#[derive(Clone)]
+pub struct SystemCompanion {
+    pub id: ID,
+    /// In solar masses.
+    pub mass: f32,
+    /// In solar luminosities.
+    pub luminosity: f32,
+    /// Semi-major axis around the star it orbits, in meters.
+    pub separation: f64,
+    pub eccentricity: f64,
+    /// Companion it orbits, `None` for the primary.
+    pub parent: Option<ID>,
+}
+
+impl From<&CompanionBundle> for SystemCompanion {
+    fn from(companion: &CompanionBundle) -> Self {
+        Self {
+            id: companion.id.clone(),
+            mass: companion.mass.0,
+            luminosity: companion.luminosity.0,
+            separation: companion.orbit_bundle.elements.semi_major_axis,
+            eccentricity: companion.orbit_bundle.elements.eccentricity,
+            parent: companion.orbit_bundle.parent.0.clone(),
+        }
+    }
+}
+
 #[derive(Component, Default)]
 pub struct Luminosity(pub f32);
 
+/// In years.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Age(pub f64);
+
+/// Years a star spends on the main sequence, infinite for remnants.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Lifetime(pub f64);
+
+/// Share of its main sequence lifetime a star then spends as a giant.
+pub static GIANT_PHASE: f64 = 0.1;
+
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
+pub enum EvolutionStage {
+    #[default]
+    MainSequence,
+    /// Swollen after running out of core hydrogen, subgiants included.
+    Giant,
+    Remnant,
+}
+
+impl Display for EvolutionStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MainSequence => write!(f, "main sequence"),
+            Self::Giant => write!(f, "giant"),
+            Self::Remnant => write!(f, "remnant"),
+        }
+    }
+}
+
+#[derive(Component, Default, Clone, Copy)]
+pub struct CEvolutionStage(pub EvolutionStage);
+
+/// Periodic swing of a star's output, from pulsation or starspots.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Variability {
+    /// Fractional change of luminosity either way.
+    pub amplitude: f32,
+    /// In years.
+    pub period: f64,
+    /// Fraction of a period already done at year zero.
+    pub phase: f64,
+}
+
+impl Variability {
+    /// Luminosity multiplier at game time `years`.
+    pub fn factor(&self, years: f64) -> f32 {
+        if self.period <= 0.0 {
+            return 1.0;
+        }
+        let angle = std::f64::consts::TAU * (years / self.period + self.phase);
+        1.0 + self.amplitude * angle.sin() as f32
+    }
+}
+
+#[derive(Component, Default, Clone, Copy)]
+pub struct FlareActivity {
+    /// Flares per year.
+    pub rate: f32,
+    /// Typical fractional rise of luminosity at a flare's peak.
+    pub strength: f32,
+}
+
+/// Current multiplier on `Luminosity` from variability and flares.
+#[derive(Component, Clone, Copy)]
+pub struct LuminosityModifier(pub f32);
+
+impl Default for LuminosityModifier {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Flare in progress.
+#[derive(Component, Clone, Copy)]
+pub struct ActiveFlare {
+    /// Fractional rise of luminosity.
+    pub boost: f32,
+    /// Step of game time, counted in flare durations, the flare lasts for.
+    pub step: u64,
+}
+
+/// Orbits where a planet could keep liquid surface water, in meters.
+#[derive(Component, Default, Clone, Copy)]
+pub struct HabitableZone {
+    pub inner: f64,
+    pub outer: f64,
+}
+
+impl HabitableZone {
+    /// The Sun's conservative 0.95–1.37 AU, scaled by the square root of
+    /// `luminosity`.
+    pub fn around(luminosity: f32) -> Self {
+        let scale = (luminosity as f64).sqrt() * AU;
+        Self {
+            inner: 0.95 * scale,
+            outer: 1.37 * scale,
+        }
+    }
+}
+
+/// Distance beyond which water ice condenses, in meters.
+#[derive(Component, Default, Clone, Copy)]
+pub struct FrostLine(pub f64);
+
+impl FrostLine {
+    /// Where a black body cools to 170 K.
+    pub fn around(luminosity: f32) -> Self {
+        Self(2.7 * (luminosity as f64).sqrt() * AU)
+    }
+}
+
+#[derive(Component, Default)]
+pub struct CatalogueDesignation(pub String);
+
+/// [Fe/H] in dex, `0.0` is solar.
+#[derive(Component, Default, Clone, Copy)]
+pub struct Metallicity(pub f32);
+
+/// Index into `GalaxySectors::sectors`.
+#[derive(Component, Clone, Copy)]
+pub struct SectorId(pub usize);
+
+/// Supermassive black hole at the galactic centre.
+#[derive(Component, Default)]
+pub struct SupermassiveBlackHole;
+
+#[derive(Bundle)]
+pub struct SupermassiveBlackHoleBundle {
+    pub id: ID,
+    pub name: Name,
+    pub mass: Mass,
+    pub spatial: SpatialBundle,
+    pub black_hole: SupermassiveBlackHole,
+}
+
+/// Stellar population a star is drawn from.
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
+pub enum StellarPopulation {
+    #[default]
+    Disk,
+    /// Old core population without young hot stars.
+    Bulge,
+    /// Young open cluster, rich in hot O/B/A stars.
+    OpenCluster,
+    /// Old globular cluster, dominated by K/M stars and their giants.
+    Globular,
+}
+
 #[derive(PartialEq, Clone, Copy, Default)]
 pub enum StarType {
     O,
@@ -34,6 +286,59 @@ pub enum StarType {
     K,
     #[default]
     M,
+    WhiteDwarf,
+    NeutronStar,
+    /// Neutron star whose beams sweep past us.
+    Pulsar,
+    BlackHole,
+}
+
+impl StarType {
+    pub fn letter(&self) -> &'static str {
+        match self {
+            Self::O => "O",
+            Self::B => "B",
+            Self::A => "A",
+            Self::F => "F",
+            Self::G => "G",
+            Self::K => "K",
+            Self::M => "M",
+            Self::WhiteDwarf => "D",
+            Self::NeutronStar => "N",
+            Self::Pulsar => "P",
+            Self::BlackHole => "X",
+        }
+    }
+
+    /// Dead star left behind once fusion has stopped.
+    pub fn is_remnant(&self) -> bool {
+        matches!(
+            self,
+            Self::WhiteDwarf | Self::NeutronStar | Self::Pulsar | Self::BlackHole
+        )
+    }
+}
+
+impl FromStr for StarType {
+    type Err = ParseSpectralError;
+
+    /// Inverse of `letter`, in either case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "O" => Ok(Self::O),
+            "B" => Ok(Self::B),
+            "A" => Ok(Self::A),
+            "F" => Ok(Self::F),
+            "G" => Ok(Self::G),
+            "K" => Ok(Self::K),
+            "M" => Ok(Self::M),
+            "D" => Ok(Self::WhiteDwarf),
+            "N" => Ok(Self::NeutronStar),
+            "P" => Ok(Self::Pulsar),
+            "X" => Ok(Self::BlackHole),
+            _ => Err(ParseSpectralError),
+        }
+    }
 }
 
 impl Display for StarType {
@@ -46,6 +351,10 @@ impl Display for StarType {
             Self::G => "G type (yellow orange)",
             Self::K => "K type (orange red)",
             Self::M => "M type (red)",
+            Self::WhiteDwarf => "white dwarf",
+            Self::NeutronStar => "neutron star",
+            Self::Pulsar => "pulsar",
+            Self::BlackHole => "black hole",
         };
         write!(f, "{}", symbol);
 
@@ -53,26 +362,34 @@ impl Display for StarType {
     }
 }
 
+/// Yerkes luminosity class. The numerals are not acronyms.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(PartialEq, Default, Clone, Copy)]
 pub enum StarClass {
     O,
-    I,
+    Ia,
+    Ib,
     II,
     III,
     IV,
     #[default]
     V,
+    /// Degenerate remnants. Historically the white dwarf class, used here for
+    /// every remnant.
+    VII,
 }
 
 impl Display for StarClass {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let symbol = match self {
             Self::O => "O class (hypergiant)",
-            Self::I => "I class (supergiant)",
+            Self::Ia => "Ia class (luminous supergiant)",
+            Self::Ib => "Ib class (supergiant)",
             Self::II => "II class (bright giant)",
             Self::III => "III class (giant)",
             Self::IV => "IV class (subdwarf)",
             Self::V => "V class (dwarf)",
+            Self::VII => "VII class (degenerate)",
         };
         write!(f, "{}", symbol);
 
@@ -80,9 +397,167 @@ impl Display for StarClass {
     }
 }
 
+impl StarClass {
+    pub const ALL: [Self; 8] = [
+        Self::O,
+        Self::Ia,
+        Self::Ib,
+        Self::II,
+        Self::III,
+        Self::IV,
+        Self::V,
+        Self::VII,
+    ];
+
+    /// Yerkes numeral, hypergiants written `Ia+`.
+    pub fn numeral(&self) -> &'static str {
+        match self {
+            Self::O => "Ia+",
+            Self::Ia => "Ia",
+            Self::Ib => "Ib",
+            Self::II => "II",
+            Self::III => "III",
+            Self::IV => "IV",
+            Self::V => "V",
+            Self::VII => "VII",
+        }
+    }
+}
+
+impl FromStr for StarClass {
+    type Err = ParseSpectralError;
+
+    /// Yerkes numeral. The intermediate `Iab` counts as `Ia` and subdwarfs
+    /// (`VI`), just under the main sequence, as `V`. Case is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "0" | "IA0" | "IA+" => Ok(Self::O),
+            "IA" | "IAB" => Ok(Self::Ia),
+            "I" | "IB" => Ok(Self::Ib),
+            "II" => Ok(Self::II),
+            "III" => Ok(Self::III),
+            "IV" => Ok(Self::IV),
+            "V" | "VI" => Ok(Self::V),
+            "VII" => Ok(Self::VII),
+            _ => Err(ParseSpectralError),
+        }
+    }
+}
+
+/// Morgan–Keenan designation such as `G2V`, `K5III` or `B8Ia`. White
+/// dwarfs are written `D` and their temperature index, other remnants by
+/// name.
+#[derive(PartialEq, Clone, Copy, Default)]
+pub struct SpectralDesignation {
+    pub star_type: StarType,
+    /// From `0.0` for the hottest to `9.5` for the coolest of the type.
+    pub subclass: f32,
+    pub star_class: StarClass,
+}
+
+#[derive(Debug)]
+pub struct ParseSpectralError;
+
+impl Display for ParseSpectralError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid spectral designation")
+    }
+}
+
+impl Display for SpectralDesignation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.star_type {
+            StarType::WhiteDwarf => write!(f, "D{}", self.subclass),
+            StarType::NeutronStar | StarType::Pulsar | StarType::BlackHole => {
+                write!(f, "{}", self.star_type)
+            }
+            _ => write!(
+                f,
+                "{}{}{}",
+                self.star_type.letter(),
+                self.subclass,
+                self.star_class.numeral()
+            ),
+        }
+    }
+}
+
+impl FromStr for SpectralDesignation {
+    type Err = ParseSpectralError;
+
+    /// Inverse of `Display`, also taking white dwarf composition letters
+    /// (`DA2`), in either case. A bare remnant letter (`N`, `P`, `X`) reads as
+    /// the remnant, otherwise a missing subclass is read as `5` and a missing
+    /// luminosity class as a dwarf.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(star_type) = [StarType::NeutronStar, StarType::Pulsar, StarType::BlackHole]
+            .into_iter()
+            .find(|t| t.to_string().eq_ignore_ascii_case(s))
+        {
+            return Ok(Self {
+                star_type,
+                subclass: 0.0,
+                star_class: StarClass::VII,
+            });
+        }
+
+        let (star_type, rest) = match s.strip_prefix(['D', 'd']) {
+            Some(rest) => (
+                StarType::WhiteDwarf,
+                rest.trim_start_matches(|c: char| c.is_ascii_alphabetic()),
+            ),
+            None => {
+                let letter = s.get(..1).ok_or(ParseSpectralError)?;
+                (letter.parse()?, &s[1..])
+            }
+        };
+        if matches!(
+            star_type,
+            StarType::NeutronStar | StarType::Pulsar | StarType::BlackHole
+        ) {
+            if !rest.is_empty() {
+                return Err(ParseSpectralError);
+            }
+            return Ok(Self {
+                star_type,
+                subclass: 0.0,
+                star_class: StarClass::VII,
+            });
+        }
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let (subclass, star_class) = rest.split_at(split);
+
+        let subclass = match subclass {
+            "" => 5.0,
+            subclass => subclass.parse().map_err(|_| ParseSpectralError)?,
+        };
+        if star_type != StarType::WhiteDwarf && !(0.0..10.0).contains(&subclass) {
+            return Err(ParseSpectralError);
+        }
+        let star_class = match star_class {
+            "" if star_type == StarType::WhiteDwarf => StarClass::VII,
+            "" => StarClass::V,
+            star_class => star_class.parse()?,
+        };
+
+        Ok(Self {
+            star_type,
+            subclass,
+            star_class,
+        })
+    }
+}
+
 #[derive(Component, Default)]
 pub struct CStarType(pub StarType);
 
+/// Number after the spectral letter, see `SpectralDesignation::subclass`.
+#[derive(Component, Default, Clone, Copy)]
+pub struct SpectralSubclass(pub f32);
+
 #[derive(Component, Default)]
 pub struct CStarClass(pub StarClass);
 
@@ -92,44 +567,610 @@ impl StarBundle {
         self
     }
 
-    pub fn with_temperature(mut self, temperature: f32) -> Self {
-        self.temperature = Temperature(temperature);
+    pub fn with_designation(mut self, designation: &str) -> Self {
+        self.designation = CatalogueDesignation(designation.to_string());
         self
     }
 
-    pub fn with_radius(mut self, radius: f32) -> Self {
-        self.radius = Radius(radius);
+    pub fn with_metallicity(mut self, metallicity: f32) -> Self {
+        self.metallicity = Metallicity(metallicity);
         self
     }
 }
 
+/// Observable properties of a star at one point of its evolution.
+pub struct StellarState {
+    pub star_type: StarType,
+    pub subclass: f32,
+    pub star_class: StarClass,
+    /// In solar masses.
+    pub mass: f32,
+    /// In solar luminosities.
+    pub luminosity: f32,
+    /// In solar radii.
+    pub radius: f32,
+    /// In kelvins.
+    pub temperature: f32,
+}
+
+static SOLAR_TEMPERATURE: f32 = 5772.0;
+static SOLAR_RADIUS_KM: f32 = 695_700.0;
+static SOLAR_RADIUS_AU: f64 = 0.00465;
+
 impl Star {
-    pub fn generate() -> StarBundle {
-        let star_type = Self::get_star_type();
-        let star_class = Self::get_star_class(star_type);
-        let luminosity = Self::get_luminosity(star_class, star_type);
+    pub fn generate(rng: &mut impl Rng, population: StellarPopulation) -> StarBundle {
+        if let Some(remnant) = Self::get_remnant_type(rng, population) {
+            return Self::generate_remnant(rng, remnant, population);
+        }
+
+        let star_type = Self::get_star_type(rng, population);
+        let star_class = Self::get_star_class(rng, star_type, population);
+        let luminosity = Self::get_luminosity(rng, star_class, star_type);
         let temperature_classification =
-            Self::get_temperature_classification(star_class, star_type);
-        let temperature = Self::get_temperature(star_type, temperature_classification);
+            Self::get_temperature_classification(rng, star_class, star_type);
+        let temperature = Self::get_temperature(rng, star_type, temperature_classification);
         let mass = Self::get_mass(luminosity);
         let radius = Self::get_radius(luminosity, temperature);
+        let lifetime = Self::get_lifetime(mass);
+        let (age, stage) = Self::get_age(rng, population, star_class, lifetime);
+        let (variability, flares) = Self::get_variability(rng, star_type, star_class);
 
         StarBundle {
-            id: ID::default(),
+            id: ID::from_rng(rng),
             name: Name("Star".to_string()),
+            designation: CatalogueDesignation::default(),
             star_type: CStarType(star_type),
+            subclass: SpectralSubclass(temperature_classification as f32),
             star_class: CStarClass(star_class),
             temperature: Temperature(temperature),
             radius: Radius(radius),
             luminosity: Luminosity(luminosity),
+            habitable_zone: HabitableZone::around(luminosity),
+            frost_line: FrostLine::around(luminosity),
+            metallicity: Metallicity::default(),
             mass: Mass(mass),
+            age: Age(age),
+            lifetime: Lifetime(lifetime),
+            stage: CEvolutionStage(stage),
+            variability,
+            flares,
+            luminosity_modifier: LuminosityModifier::default(),
             star: Star,
         }
     }
 
-    fn get_star_type() -> StarType {
-        let mut rng = rng();
-        let star_type_proba = rng.random_range(1..=1000);
+    /// Stars bound to `primary`: none, a companion, or a hierarchical triple
+    /// with a third star either around the pair or close to the companion.
+    /// Hot massive stars are the most likely to have company.
+    pub fn generate_companions(
+        rng: &mut impl Rng,
+        population: StellarPopulation,
+        primary: &StarBundle,
+    ) -> Vec<CompanionBundle> {
+        let multiplicity = match primary.star_type.0 {
+            StarType::O | StarType::B => 0.7,
+            StarType::A => 0.5,
+            StarType::F | StarType::G => 0.45,
+            StarType::K => 0.35,
+            StarType::M => 0.25,
+            _ => 0.2,
+        };
+        let mut companions = Vec::new();
+        if !rng.random_bool(multiplicity) {
+            return companions;
+        }
+
+        let Some(second) = Self::generate_companion_star(rng, population, primary.mass.0) else {
+            return companions;
+        };
+        let separation = Self::get_binary_separation(
+            rng,
+            primary.mass.0 + second.mass.0,
+            primary.radius.0 + second.radius.0,
+        );
+        let pair_mass = primary.mass.0 + second.mass.0;
+        let contact = 2.0 * (primary.radius.0 + second.radius.0) as f64 * SOLAR_RADIUS_AU;
+        let orbit = Self::get_binary_orbit(rng, separation, pair_mass, contact);
+        let second = Self::companion(second, &primary.id, None, orbit, pair_mass);
+
+        let third = if rng.random_bool(0.25) {
+            if rng.random_bool(0.5) {
+                // Wide enough around the inner pair to stay hierarchical.
+                Self::generate_companion_star(rng, population, primary.mass.0).map(|star| {
+                    let wide = separation * 10f64.powf(rng.random_range(0.7..2.5));
+                    let mass = pair_mass + star.mass.0;
+                    // Periapsis stays clear of the inner pair.
+                    let orbit = Self::get_binary_orbit(rng, wide, mass, 3.0 * separation);
+                    Self::companion(star, &primary.id, None, orbit, mass)
+                })
+            } else {
+                Self::generate_companion_star(rng, population, second.mass.0)
+                    .map(|star| {
+                        let close = separation / 10f64.powf(rng.random_range(0.7..2.0));
+                        let contact =
+                            2.0 * (second.radius.0 + star.radius.0) as f64 * SOLAR_RADIUS_AU;
+                        (star, close.max(contact))
+                    })
+                    .filter(|(_, close)| *close < separation / 3.0)
+                    .map(|(star, close)| {
+                        let mass = second.mass.0 + star.mass.0;
+                        let contact =
+                            2.0 * (second.radius.0 + star.radius.0) as f64 * SOLAR_RADIUS_AU;
+                        let orbit = Self::get_binary_orbit(rng, close, mass, contact);
+                        let parent = Some(second.id.clone());
+                        Self::companion(star, &primary.id, parent, orbit, mass)
+                    })
+            }
+        } else {
+            None
+        };
+
+        companions.push(second);
+        companions.extend(third);
+        companions
+    }
+
+    /// Star no heavier than `max_mass`, or `None` if the draws keep coming
+    /// out heavier.
+    fn generate_companion_star(
+        rng: &mut impl Rng,
+        population: StellarPopulation,
+        max_mass: f32,
+    ) -> Option<StarBundle> {
+        (0..16)
+            .map(|_| Self::generate(rng, population))
+            .find(|star| star.mass.0 <= max_mass)
+    }
+
+    /// Semi-major axis in AU from the log-normal period distribution of
+    /// solar-type binaries, kept clear of contact.
+    fn get_binary_separation(rng: &mut impl Rng, total_mass: f32, total_radius: f32) -> f64 {
+        let contact = 2.0 * total_radius as f64 * SOLAR_RADIUS_AU;
+        for _ in 0..16 {
+            let log_days = 5.03 + 2.28 * rng.sample::<f64, _>(StandardNormal);
+            let years = 10f64.powf(log_days.clamp(-0.5, 10.0)) / 365.25;
+            let separation = (total_mass as f64 * years * years).cbrt();
+            if separation > contact {
+                return separation;
+            }
+        }
+        5.0 * contact
+    }
+
+    /// Orbit with a semi-major axis of `separation` AU. Pairs closer than
+    /// ten days are circularised by tides, wider ones get a roughly flat
+    /// eccentricity distribution (Raghavan et al. 2010), kept so periapsis
+    /// stays beyond `contact` AU. Orientation is isotropic.
+    fn get_binary_orbit(
+        rng: &mut impl Rng,
+        separation: f64,
+        total_mass: f32,
+        contact: f64,
+    ) -> OrbitalElements {
+        let period = OrbitPeriod::kepler(separation * AU, total_mass as f64).0;
+        let eccentricity = if period * 365.25 < 10.0 {
+            0.0
+        } else {
+            rng.random_range(0.0..0.8f64)
+                .min(1.0 - contact / separation)
+                .max(0.0)
+        };
+        OrbitalElements {
+            semi_major_axis: separation * AU,
+            eccentricity,
+            inclination: rng.random_range(-1.0..1.0f64).acos(),
+            argument_of_periapsis: rng.random_range(0.0..std::f64::consts::TAU),
+            longitude_of_ascending_node: rng.random_range(0.0..std::f64::consts::TAU),
+            mean_anomaly: rng.random_range(0.0..std::f64::consts::TAU),
+        }
+    }
+
+    /// `host_mass` is the mass in solar masses inside the companion's orbit
+    /// plus its own.
+    fn companion(
+        star: StarBundle,
+        system: &ID,
+        parent: Option<ID>,
+        orbit: OrbitalElements,
+        host_mass: f32,
+    ) -> CompanionBundle {
+        CompanionBundle {
+            id: star.id,
+            name: star.name,
+            star_type: star.star_type,
+            subclass: star.subclass,
+            star_class: star.star_class,
+            luminosity: star.luminosity,
+            habitable_zone: star.habitable_zone,
+            frost_line: star.frost_line,
+            temperature: star.temperature,
+            radius: star.radius,
+            mass: star.mass,
+            age: star.age,
+            lifetime: star.lifetime,
+            stage: star.stage,
+            variability: star.variability,
+            flares: star.flares,
+            luminosity_modifier: star.luminosity_modifier,
+            system_id: SystemId(system.clone()),
+            body_type: BodyType(BodyTypes::Star),
+            orbit_bundle: OrbitBundle {
+                orbit_period: OrbitPeriod::kepler(orbit.semi_major_axis, host_mass as f64),
+                elements: orbit,
+                parent: Parent(parent),
+            },
+            companion: Companion,
+        }
+    }
+
+    /// Star with a known spectrum, `abs_magnitude` being the absolute
+    /// visual magnitude.
+    pub fn from_catalogue(
+        rng: &mut impl Rng,
+        spectral: SpectralDesignation,
+        abs_magnitude: f32,
+    ) -> StarBundle {
+        let SpectralDesignation {
+            star_type,
+            subclass,
+            star_class,
+        } = spectral;
+        let luminosity = 10f32.powf((4.83 - abs_magnitude) / 2.5);
+        let (temperature, mass) = match star_type {
+            // White dwarf subclasses are 50 400 K over the temperature.
+            StarType::WhiteDwarf => (50_400.0 / subclass.max(0.1), 0.6),
+            // Too faint for the magnitude to say much, so drawn like any
+            // other neutron star or black hole.
+            _ if star_type.is_remnant() => {
+                return Self::generate_remnant(rng, star_type, StellarPopulation::Disk);
+            }
+            _ => {
+                let (_, temp_min) = Self::get_temp_maxmin(star_type);
+                let temperature =
+                    temp_min + Self::get_temp_coeff(star_type) * (9.0 - subclass.clamp(0.0, 9.0));
+                (temperature, Self::get_mass(luminosity))
+            }
+        };
+        let (variability, flares) = Self::get_variability(rng, star_type, star_class);
+        let (lifetime, age, stage) = match star_type {
+            StarType::WhiteDwarf => (
+                f64::INFINITY,
+                rng.random_range(0.0..Self::max_age(StellarPopulation::Disk)),
+                EvolutionStage::Remnant,
+            ),
+            _ => {
+                let lifetime = Self::get_lifetime(mass);
+                let (age, stage) =
+                    Self::get_age(rng, StellarPopulation::Disk, star_class, lifetime);
+                (lifetime, age, stage)
+            }
+        };
+
+        StarBundle {
+            id: ID::from_rng(rng),
+            star_type: CStarType(star_type),
+            subclass: SpectralSubclass(subclass),
+            star_class: CStarClass(star_class),
+            temperature: Temperature(temperature),
+            radius: Radius(Self::get_radius(luminosity, temperature)),
+            luminosity: Luminosity(luminosity),
+            habitable_zone: HabitableZone::around(luminosity),
+            frost_line: FrostLine::around(luminosity),
+            mass: Mass(mass),
+            age: Age(age),
+            lifetime: Lifetime(lifetime),
+            stage: CEvolutionStage(stage),
+            variability,
+            flares,
+            ..default()
+        }
+    }
+
+    /// Remnant drawn in place of a living star, per mille: old populations
+    /// have had time to leave more white dwarfs, and globular clusters are
+    /// full of recycled millisecond pulsars.
+    fn get_remnant_type(rng: &mut impl Rng, population: StellarPopulation) -> Option<StarType> {
+        let (white_dwarfs, neutron_stars, pulsars, black_holes) = match population {
+            StellarPopulation::Disk => (60, 3, 1, 2),
+            StellarPopulation::Bulge => (80, 4, 1, 3),
+            StellarPopulation::OpenCluster => (10, 2, 2, 1),
+            StellarPopulation::Globular => (120, 4, 6, 2),
+        };
+
+        let roll = rng.random_range(0..1000);
+        if roll < white_dwarfs {
+            Some(StarType::WhiteDwarf)
+        } else if roll < white_dwarfs + neutron_stars {
+            Some(StarType::NeutronStar)
+        } else if roll < white_dwarfs + neutron_stars + pulsars {
+            Some(StarType::Pulsar)
+        } else if roll < white_dwarfs + neutron_stars + pulsars + black_holes {
+            Some(StarType::BlackHole)
+        } else {
+            None
+        }
+    }
+
+    fn generate_remnant(
+        rng: &mut impl Rng,
+        star_type: StarType,
+        population: StellarPopulation,
+    ) -> StarBundle {
+        let (mass, radius, temperature) = match star_type {
+            StarType::WhiteDwarf => {
+                let mass = (0.6 + 0.15 * rng.sample::<f32, _>(StandardNormal)).clamp(0.17, 1.33);
+                // Cooling is slow at the faint end, so most are cool.
+                let temperature = 4000.0 * 10f32.powf(rng.random::<f32>().powi(2));
+                (mass, Self::white_dwarf_radius(mass), temperature)
+            }
+            StarType::NeutronStar | StarType::Pulsar => {
+                let mass = (1.4 + 0.1 * rng.sample::<f32, _>(StandardNormal)).clamp(1.1, 2.2);
+                let radius = rng.random_range(10.0..14.0) / SOLAR_RADIUS_KM;
+                let temperature = 10f32.powf(rng.random_range(5.0..6.0));
+                (mass, radius, temperature)
+            }
+            _ => {
+                let mass = rng
+                    .sample(LogNormal::new(2.1f32, 0.35).unwrap())
+                    .clamp(3.0, 60.0);
+                (mass, Self::schwarzschild_radius(mass), 0.0)
+            }
+        };
+        let luminosity = Self::get_blackbody_luminosity(radius, temperature);
+        let subclass = Self::get_remnant_subclass(star_type, temperature);
+        let max_age = Self::max_age(population);
+
+        StarBundle {
+            id: ID::from_rng(rng),
+            star_type: CStarType(star_type),
+            subclass: SpectralSubclass(subclass),
+            star_class: CStarClass(StarClass::VII),
+            temperature: Temperature(temperature),
+            radius: Radius(radius),
+            luminosity: Luminosity(luminosity),
+            habitable_zone: HabitableZone::around(luminosity),
+            frost_line: FrostLine::around(luminosity),
+            mass: Mass(mass),
+            age: Age(rng.random_range(0.1 * max_age..max_age)),
+            lifetime: Lifetime(f64::INFINITY),
+            stage: CEvolutionStage(EvolutionStage::Remnant),
+            ..default()
+        }
+    }
+
+    /// Main sequence lifetime in years, ten billion for the Sun.
+    pub fn get_lifetime(mass: f32) -> f64 {
+        1e10 * (mass as f64).powf(-2.5)
+    }
+
+    /// Oldest star a population has, in years.
+    fn max_age(population: StellarPopulation) -> f64 {
+        match population {
+            StellarPopulation::Disk => 10e9,
+            StellarPopulation::Bulge => 12e9,
+            StellarPopulation::OpenCluster => 0.3e9,
+            StellarPopulation::Globular => 12.5e9,
+        }
+    }
+
+    /// Age consistent with the luminosity class: dwarfs are still on the
+    /// main sequence, subgiants about to leave it, brighter classes already
+    /// giants.
+    fn get_age(
+        rng: &mut impl Rng,
+        population: StellarPopulation,
+        star_class: StarClass,
+        lifetime: f64,
+    ) -> (f64, EvolutionStage) {
+        match star_class {
+            StarClass::V => (
+                rng.random_range(0.0..Self::max_age(population).min(lifetime)),
+                EvolutionStage::MainSequence,
+            ),
+            StarClass::IV => (
+                lifetime * rng.random_range(0.9..1.0),
+                EvolutionStage::MainSequence,
+            ),
+            _ => (
+                lifetime * (1.0 + GIANT_PHASE * rng.random_range(0.0..1.0)),
+                EvolutionStage::Giant,
+            ),
+        }
+    }
+
+    /// Pulsation, spot modulation and flaring typical of the star's kind.
+    pub fn get_variability(
+        rng: &mut impl Rng,
+        star_type: StarType,
+        star_class: StarClass,
+    ) -> (Variability, FlareActivity) {
+        let phase = rng.random::<f64>();
+        let giant = !matches!(star_class, StarClass::V | StarClass::IV);
+        match star_type {
+            _ if star_type.is_remnant() => (Variability::default(), FlareActivity::default()),
+            // Red dwarfs: spots rotating in and out of view, and frequent
+            // flares that can briefly double their output.
+            StarType::M if !giant => (
+                Variability {
+                    amplitude: rng.random_range(0.005..0.05),
+                    period: rng.random_range(0.002..0.3),
+                    phase,
+                },
+                FlareActivity {
+                    rate: 10f32.powf(rng.random_range(0.0..1.7)),
+                    strength: rng.random_range(0.05..1.0),
+                },
+            ),
+            // Long-period variables such as Mira.
+            StarType::K | StarType::M if giant => (
+                Variability {
+                    amplitude: rng.random_range(0.1..0.6),
+                    period: rng.random_range(0.3..3.0),
+                    phase,
+                },
+                FlareActivity::default(),
+            ),
+            // Cepheids.
+            StarType::F | StarType::G if giant => (
+                Variability {
+                    amplitude: rng.random_range(0.1..0.3),
+                    period: rng.random_range(0.003..0.14),
+                    phase,
+                },
+                FlareActivity::default(),
+            ),
+            // Sun-like activity cycles with the odd weak flare.
+            StarType::F | StarType::G | StarType::K => (
+                Variability {
+                    amplitude: rng.random_range(0.0005..0.002),
+                    period: rng.random_range(8.0..14.0),
+                    phase,
+                },
+                FlareActivity {
+                    rate: rng.random_range(0.01..1.0),
+                    strength: rng.random_range(0.0001..0.001),
+                },
+            ),
+            _ => (
+                Variability {
+                    amplitude: rng.random_range(0.0..0.01),
+                    period: rng.random_range(0.01..1.0),
+                    phase,
+                },
+                FlareActivity::default(),
+            ),
+        }
+    }
+
+    /// Giant a star of `mass` swells into once its core hydrogen is spent.
+    /// Stars heavy enough to end in a supernova become red supergiants.
+    pub fn giant(mass: f32, supernova_mass: f32) -> StellarState {
+        let (star_class, luminosity, temperature) = if mass >= supernova_mass {
+            let star_class = if mass >= 2.5 * supernova_mass {
+                StarClass::Ia
+            } else {
+                StarClass::Ib
+            };
+            (star_class, 1e4 * (mass / supernova_mass).powi(2), 3800.0)
+        } else {
+            (StarClass::III, 100.0 * mass.powi(2), 4500.0)
+        };
+        let (star_type, subclass) = Self::classify_temperature(temperature);
+
+        StellarState {
+            star_type,
+            subclass,
+            star_class,
+            mass,
+            luminosity,
+            radius: Self::get_radius(luminosity, temperature),
+            temperature,
+        }
+    }
+
+    /// What is left of a giant of `mass`: a white dwarf below
+    /// `supernova_mass`, a young fast-spinning pulsar below
+    /// `black_hole_mass`, a black hole above.
+    pub fn remnant(mass: f32, supernova_mass: f32, black_hole_mass: f32) -> StellarState {
+        let (star_type, remnant_mass, radius, temperature) = if mass < supernova_mass {
+            let remnant_mass = (0.5 + 0.07 * (mass - 1.0)).clamp(0.5, 1.3);
+            (
+                StarType::WhiteDwarf,
+                remnant_mass,
+                Self::white_dwarf_radius(remnant_mass),
+                25_000.0,
+            )
+        } else if mass < black_hole_mass {
+            (StarType::Pulsar, 1.4, 12.0 / SOLAR_RADIUS_KM, 1e6)
+        } else {
+            // Most of the envelope is blown away before the collapse.
+            let remnant_mass = 0.3 * mass;
+            (
+                StarType::BlackHole,
+                remnant_mass,
+                Self::schwarzschild_radius(remnant_mass),
+                0.0,
+            )
+        };
+
+        StellarState {
+            star_type,
+            subclass: Self::get_remnant_subclass(star_type, temperature),
+            star_class: StarClass::VII,
+            mass: remnant_mass,
+            luminosity: Self::get_blackbody_luminosity(radius, temperature),
+            radius,
+            temperature,
+        }
+    }
+
+    /// Spectral letter and subclass of a photosphere at `temperature`.
+    pub fn classify_temperature(temperature: f32) -> (StarType, f32) {
+        [
+            StarType::O,
+            StarType::B,
+            StarType::A,
+            StarType::F,
+            StarType::G,
+            StarType::K,
+            StarType::M,
+        ]
+        .into_iter()
+        .map(|star_type| {
+            let (_, temp_min) = Self::get_temp_maxmin(star_type);
+            let subclass = 9.0 - (temperature - temp_min) / Self::get_temp_coeff(star_type);
+            (star_type, subclass.round())
+        })
+        .find(|(_, subclass)| *subclass >= 0.0 && *subclass <= 9.0)
+        .unwrap_or(if temperature > 30_000.0 {
+            (StarType::O, 0.0)
+        } else {
+            (StarType::M, 9.0)
+        })
+    }
+
+    /// Luminosity of a sphere of `radius` solar radii at `temperature`.
+    pub fn get_blackbody_luminosity(radius: f32, temperature: f32) -> f32 {
+        radius.powi(2) * (temperature / SOLAR_TEMPERATURE).powi(4)
+    }
+
+    /// White dwarf temperature index to the nearest half, `0.0` for other
+    /// remnants.
+    pub fn get_remnant_subclass(star_type: StarType, temperature: f32) -> f32 {
+        match star_type {
+            StarType::WhiteDwarf => (2.0 * 50_400.0 / temperature).round() / 2.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Nauenberg's mass-radius relation, in solar radii.
+    pub fn white_dwarf_radius(mass: f32) -> f32 {
+        let ratio = (mass / 1.44).min(0.999).powf(2.0 / 3.0);
+        0.0112 * (1.0 / ratio - ratio).sqrt()
+    }
+
+    /// Event horizon, in solar radii.
+    pub fn schwarzschild_radius(mass: f32) -> f32 {
+        2.953 * mass / SOLAR_RADIUS_KM
+    }
+
+    fn get_star_type(rng: &mut impl Rng, population: StellarPopulation) -> StarType {
+        let star_type_proba = match population {
+            StellarPopulation::Disk => rng.random_range(1..=1000),
+            StellarPopulation::Bulge => rng.random_range(12..=1000),
+            StellarPopulation::OpenCluster => {
+                return match rng.random_range(1..=100) {
+                    1..=5 => StarType::O,
+                    6..=30 => StarType::B,
+                    31..=60 => StarType::A,
+                    61..=75 => StarType::F,
+                    76..=85 => StarType::G,
+                    86..=92 => StarType::K,
+                    _ => StarType::M,
+                }
+            }
+            StellarPopulation::Globular => rng.random_range(40..=1000),
+        };
 
         match star_type_proba {
             1..=2 => StarType::O,
@@ -142,8 +1183,19 @@ impl Star {
         }
     }
 
-    fn get_star_class(star_type: StarType) -> StarClass {
-        let mut rng = rng();
+    fn get_star_class(
+        rng: &mut impl Rng,
+        star_type: StarType,
+        population: StellarPopulation,
+    ) -> StarClass {
+        // Evolved cool stars make up much of an old cluster's light.
+        if population == StellarPopulation::Globular
+            && matches!(star_type, StarType::K | StarType::M)
+            && rng.random_range(1..=100) <= 35
+        {
+            return StarClass::III;
+        }
+
         let star_class_proba = rng.random_range(1..=1000);
 
         match star_class_proba {
@@ -154,7 +1206,8 @@ impl Star {
                     StarClass::V
                 }
             }
-            3..=10 => StarClass::I,
+            3..=5 => StarClass::Ia,
+            6..=10 => StarClass::Ib,
             11..=60 => StarClass::II,
             61..=190 => StarClass::III,
             191..=200 => {
@@ -174,8 +1227,17 @@ impl Star {
             StarClass::IV => Self::get_lum_iv(star_type),
             StarClass::III => Self::get_lum_iii(star_type),
             StarClass::II => Self::get_lum_ii(star_type),
-            StarClass::I => Self::get_lum_i(star_type),
+            // Split the supergiant range at its geometric middle.
+            StarClass::Ia => {
+                let lum = Self::get_lum_i(star_type);
+                (lum.start() * lum.end()).sqrt()..=*lum.end()
+            }
+            StarClass::Ib => {
+                let lum = Self::get_lum_i(star_type);
+                *lum.start()..=(lum.start() * lum.end()).sqrt()
+            }
             StarClass::O => Self::get_lum_o(star_type),
+            StarClass::VII => unreachable!("remnants have their own models"),
         }
     }
 
@@ -188,6 +1250,7 @@ impl Star {
             StarType::G => 0.42..=1.26,
             StarType::K => 0.072..=0.42,
             StarType::M => 0.000_015..=0.072,
+            _ => unreachable!("remnants have their own models"),
         }
     }
 
@@ -200,6 +1263,7 @@ impl Star {
             StarType::G => 8.0..=9.0,
             StarType::K => 8.0..=9.0,
             StarType::M => 9.0..=10.0,
+            _ => unreachable!("remnants have their own models"),
         }
     }
 
@@ -212,6 +1276,7 @@ impl Star {
             StarType::G => 95.0..=96.0,
             StarType::K => 96.0..=98.0,
             StarType::M => 98.0..=105.0,
+            _ => unreachable!("remnants have their own models"),
         }
     }
     fn get_lum_ii(star_type: StarType) -> RangeInclusive<f32> {
@@ -223,6 +1288,7 @@ impl Star {
             StarType::G => 950.0..=960.0,
             StarType::K => 950.0..=1000.0,
             StarType::M => 1000.0..=8000.0,
+            _ => unreachable!("remnants have their own models"),
         }
     }
     fn get_lum_i(star_type: StarType) -> RangeInclusive<f32> {
@@ -234,6 +1300,7 @@ impl Star {
             StarType::G => 20_000.0..=500_000.0,
             StarType::K => 20_000.0..=500_000.0,
             StarType::M => 20_000.0..=500_000.0,
+            _ => unreachable!("remnants have their own models"),
         }
     }
     fn get_lum_o(star_type: StarType) -> RangeInclusive<f32> {
@@ -245,16 +1312,19 @@ impl Star {
             StarType::G => 100_000.0..=500_000.0,
             StarType::K => 100_000.0..=500_000.0,
             StarType::M => 86_000.0..=500_000.0,
+            _ => unreachable!("remnants have their own models"),
         }
     }
 
-    fn get_luminosity(star_class: StarClass, star_type: StarType) -> f32 {
-        let mut rng = rng();
+    fn get_luminosity(rng: &mut impl Rng, star_class: StarClass, star_type: StarType) -> f32 {
         rng.random_range(Self::get_lum_range(star_class, star_type))
     }
 
-    fn get_temperature_classification(star_class: StarClass, star_type: StarType) -> i32 {
-        let mut rng = rng();
+    fn get_temperature_classification(
+        rng: &mut impl Rng,
+        star_class: StarClass,
+        star_type: StarType,
+    ) -> i32 {
         let temp_classification = rng.random_range(0..=9);
         if star_type == StarType::O && star_class == StarClass::V {
             return temp_classification.max(5);
@@ -274,6 +1344,7 @@ impl Star {
             StarType::G => (5940.0, 5335.0),
             StarType::K => (5150.0, 3880.0),
             StarType::M => (3840.0, 2376.0),
+            _ => unreachable!("remnants have their own models"),
         }
     }
 
@@ -286,14 +1357,14 @@ impl Star {
             StarType::G => 67.0,
             StarType::K => 141.0,
             StarType::M => 165.0,
+            _ => unreachable!("remnants have their own models"),
         }
     }
 
-    fn get_temperature(star_type: StarType, temp_classification: i32) -> f32 {
+    fn get_temperature(rng: &mut impl Rng, star_type: StarType, temp_classification: i32) -> f32 {
         let (temp_max, temp_min) = Self::get_temp_maxmin(star_type);
         let temp_coeff = Self::get_temp_coeff(star_type);
         let surfaceTemp = temp_min + (temp_coeff * (9.0 - temp_classification as f32));
-        let mut rng = rng();
 
         if temp_classification == 0 {
             return surfaceTemp + rng.random_range(-temp_coeff / 6.0..=temp_coeff / 6.0);
@@ -318,7 +1389,106 @@ impl Star {
         (luminosity / 3200.0).powf(1.0 / 1.25)
     }
 
-    fn get_radius(luminosity: f32, surface_temp: f32) -> f32 {
+    pub fn get_radius(luminosity: f32, surface_temp: f32) -> f32 {
         (luminosity / (surface_temp / 5766.0).powi(4)).sqrt()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn catalogue_remnants() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for (name, star_type) in [
+            ("Neutron star", StarType::NeutronStar),
+            ("Pulsar", StarType::Pulsar),
+            ("Black hole", StarType::BlackHole),
+            ("N", StarType::NeutronStar),
+            ("P", StarType::Pulsar),
+            ("X", StarType::BlackHole),
+        ] {
+            let spectral: SpectralDesignation = name.parse().unwrap();
+            assert!(spectral.star_type == star_type, "{}", name);
+
+            let star = Star::from_catalogue(&mut rng, spectral, 10.0);
+            assert!(star.star_type.0 == star_type, "{}", name);
+            assert!(star.mass.0 > 1.0, "{}", name);
+            assert!(star.lifetime.0.is_infinite(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn luminosity_classes() {
+        for (numeral, star_class) in [
+            ("Ia0", StarClass::O),
+            ("Iab", StarClass::Ia),
+            ("I", StarClass::Ib),
+            ("II", StarClass::II),
+            ("III", StarClass::III),
+            ("IV", StarClass::IV),
+            ("V", StarClass::V),
+            ("VI", StarClass::V),
+            ("VII", StarClass::VII),
+        ] {
+            assert!(
+                numeral.parse::<StarClass>().ok() == Some(star_class),
+                "{}",
+                numeral
+            );
+        }
+        assert!("VIII".parse::<StarClass>().is_err());
+
+        let subdwarf: SpectralDesignation = "M1VI".parse().unwrap();
+        assert!(subdwarf.star_type == StarType::M && subdwarf.star_class == StarClass::V);
+    }
+
+    #[test]
+    fn spectral_designations() {
+        for (name, spectral) in [
+            ("G2V", (StarType::G, 2.0, StarClass::V)),
+            ("g2v", (StarType::G, 2.0, StarClass::V)),
+            ("b8ia", (StarType::B, 8.0, StarClass::Ia)),
+            ("K", (StarType::K, 5.0, StarClass::V)),
+            ("da2", (StarType::WhiteDwarf, 2.0, StarClass::VII)),
+            ("N", (StarType::NeutronStar, 0.0, StarClass::VII)),
+            ("p", (StarType::Pulsar, 0.0, StarClass::VII)),
+            ("X", (StarType::BlackHole, 0.0, StarClass::VII)),
+        ] {
+            let (star_type, subclass, star_class) = spectral;
+            let parsed: SpectralDesignation = name.parse().unwrap();
+            assert!(
+                parsed
+                    == SpectralDesignation {
+                        star_type,
+                        subclass,
+                        star_class,
+                    },
+                "{}",
+                name
+            );
+        }
+        assert!("X5".parse::<SpectralDesignation>().is_err());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        for star_type in [StarType::NeutronStar, StarType::Pulsar, StarType::BlackHole] {
+            let spectral: SpectralDesignation = star_type.letter().parse().unwrap();
+            let star = Star::from_catalogue(&mut rng, spectral, 10.0);
+            let displayed: SpectralDesignation = spectral.to_string().parse().unwrap();
+            assert!(
+                spectral
+                    == SpectralDesignation {
+                        star_type,
+                        subclass: star.subclass.0,
+                        star_class: star.star_class.0,
+                    },
+                "{}",
+                star_type.letter()
+            );
+            assert!(displayed == spectral, "{}", star_type.letter());
+        }
+    }
+}
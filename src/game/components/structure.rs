@@ -0,0 +1,16 @@
+use bevy::prelude::Component;
+
+/// Condition of a built structure, `1.0` is undamaged and `0.0` wrecked.
+#[derive(Component, Clone, Copy)]
+pub struct Integrity(pub f32);
+
+impl Default for Integrity {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Structure out in the open, unshielded from its star's flares. Needs a
+/// `SystemId`, a `Parent` and an orbit to be hit.
+#[derive(Component, Default)]
+pub struct Exposed;
@@ -1,13 +1,23 @@
 use bevy::ecs::{component::Component, system::Resource};
 use egui::Vec2;
 
-use super::{common::ID, planet::BodyTypes};
+use super::{
+    common::ID,
+    orbit::OrbitalElements,
+    planet::{Annulus, BodyTypes, EnvironmentBundle},
+};
 
 #[derive(Resource)]
 pub struct SystemMap {
     pub system_id: Option<ID>,
     pub position: Vec2,
     pub zoom: f32,
+    /// Habitable zone of the primary, inner and outer edge in meters.
+    pub habitable_zone: Option<(f32, f32)>,
+    /// Frost line of the primary, in meters.
+    pub frost_line: Option<f32>,
+    /// Body shown in the inspector.
+    pub selected_body: Option<ID>,
 }
 
 impl Default for SystemMap {
@@ -16,15 +26,30 @@ impl Default for SystemMap {
             system_id: None,
             position: Vec2::ZERO,
             zoom: 1.0,
+            habitable_zone: None,
+            frost_line: None,
+            selected_body: None,
         }
     }
 }
 
 #[derive(Component, Clone)]
 pub struct CelestialBodyData {
+    pub id: ID,
     pub position: Vec2,
     pub orbit_center: Vec2,
-    pub orbit_radius: f32,
+    pub elements: OrbitalElements,
+    pub annulus: Annulus,
     pub body_type: BodyTypes,
     pub radius: f32,
+    /// In kg, or solar masses for stars.
+    pub mass: f32,
+    /// In years.
+    pub orbit_period: f64,
+    /// In kelvins, `None` for stars.
+    pub equilibrium_temperature: Option<f32>,
+    /// Deposit richness, see `HeavyElements`. `None` for stars.
+    pub heavy_elements: Option<f32>,
+    /// `None` for stars.
+    pub environment: Option<EnvironmentBundle>,
 }
@@ -1,16 +1,32 @@
 use crate::game::components::galaxy_map::GalaxyMap;
-use crate::ui::{ui_system, CurrentScreen, GameScreenFlags};
+use crate::ui::{in_game, ui_system, CurrentScreen, GameScreenFlags};
 use bevy::prelude::*;
 use bevy::window::{Window, WindowPlugin};
 use bevy::DefaultPlugins;
 use bevy_egui::EguiPlugin;
 use components::system_map::SystemMap;
+use resourses::evolution::EvolutionConfig;
 use resourses::galaxy::GalaxyConfig;
+use resourses::generation::GalaxyGeneration;
+use resourses::hyperlanes::HyperlaneGraph;
+use resourses::naming::NamingConfig;
+use resourses::sectors::GalaxySectors;
 use resourses::system::{SelectedSystem, SystemGenConfig};
-use systems::galaxy_generation::generate_star_systems;
-use systems::galaxy_map::update_galaxy_map;
-use systems::star_system_generation::generate_plantary_systems;
+use resourses::time::GameTime;
+use resourses::variability::VariabilityConfig;
+use systems::galaxy_generation::{spawn_generated_galaxy, GalaxyGenerated};
+use systems::galaxy_map::{update_evolved_stars, update_galaxy_map, update_star_output};
+use systems::star_system_generation::{
+    generate_pending_systems, request_selected_system, GenerateSystem,
+};
+use systems::stellar_evolution::{
+    advance_game_time, apply_supernovae, evolve_stars, rescale_equilibrium_temperatures,
+    update_environments, StarEvolved, Supernova,
+};
 use systems::system_map::update_system_map;
+use systems::variability::{
+    damage_exposed_structures, trigger_flares, update_luminosity_modifiers, StellarFlare,
+};
 
 pub mod components;
 
@@ -35,18 +51,51 @@ pub fn run_game(mut app: App) {
     app.insert_resource(CurrentScreen::default());
     app.insert_resource(GameScreenFlags::default());
     app.insert_resource(SystemGenConfig::default());
+    app.insert_resource(HyperlaneGraph::default());
+    app.insert_resource(NamingConfig::default());
+    app.insert_resource(GalaxySectors::default());
+    app.insert_resource(GalaxyGeneration::default());
+    app.insert_resource(GameTime::default());
+    app.insert_resource(EvolutionConfig::default());
+    app.insert_resource(VariabilityConfig::default());
+    app.add_event::<GalaxyGenerated>();
+    app.add_event::<StarEvolved>();
+    app.add_event::<Supernova>();
+    app.add_event::<StellarFlare>();
+    app.add_event::<GenerateSystem>();
 
     app.add_systems(
-        Startup,
+        Update,
         (
-            generate_star_systems,
-            generate_plantary_systems,
-            update_galaxy_map,
+            spawn_generated_galaxy,
+            (
+                advance_game_time,
+                evolve_stars,
+                rescale_equilibrium_temperatures,
+                update_environments,
+                apply_supernovae,
+                trigger_flares,
+                update_luminosity_modifiers,
+                damage_exposed_structures,
+            )
+                .chain()
+                .run_if(in_game),
+            update_galaxy_map.run_if(on_event::<GalaxyGenerated>),
+            update_evolved_stars.run_if(in_game),
+            update_star_output.run_if(in_game),
         )
             .chain(),
     );
     app.add_systems(Update, ui_system);
-    app.add_systems(Update, update_system_map);
+    app.add_systems(
+        Update,
+        (
+            request_selected_system,
+            generate_pending_systems,
+            update_system_map,
+        )
+            .chain(),
+    );
 
     app.run();
 }
@@ -0,0 +1,28 @@
+use bevy::prelude::Resource;
+
+#[derive(Resource, Clone)]
+pub struct EvolutionConfig {
+    /// Lightest star to end in a supernova, in solar masses.
+    pub supernova_mass: f32,
+    /// Lightest star to collapse into a black hole, in solar masses.
+    pub black_hole_mass: f32,
+    /// Orbits inside this many AU are destroyed by the star's supernova.
+    pub supernova_destruction_radius: f64,
+    /// Reach of a supernova into neighbouring systems, in map units.
+    pub supernova_blast_radius: f32,
+    /// [Fe/H] added to a neighbour right next to a supernova, fading to
+    /// nothing at the edge of the blast.
+    pub supernova_enrichment: f32,
+}
+
+impl Default for EvolutionConfig {
+    fn default() -> Self {
+        Self {
+            supernova_mass: 8.0,
+            black_hole_mass: 25.0,
+            supernova_destruction_radius: 30.0,
+            supernova_blast_radius: 150.0,
+            supernova_enrichment: 0.05,
+        }
+    }
+}
@@ -1,13 +1,61 @@
+use std::path::PathBuf;
+
 use bevy::prelude::Resource;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::game::components::common::ID;
 
-#[derive(Debug, Resource)]
+#[derive(Debug, Clone, Resource)]
 pub struct GalaxyConfig {
     pub seed: u32,
     pub num_stars: usize,
     pub galaxy_radius: f32,
-    pub arm_strength: f32,
-    pub arm_count: u32,
     pub noise_scale: f32,
+    pub shape: GalaxyShape,
+    /// Minimum distance between two stars, `0.0` places them independently.
+    pub min_star_separation: f32,
+    /// Candidates drawn per star before placement gives up on the separation.
+    pub placement_attempts: u32,
+    /// Share of stars in the central bulge.
+    pub bulge_fraction: f32,
+    /// Bulge scale radius, as a fraction of `galaxy_radius`.
+    pub bulge_radius: f32,
+    /// Exponential disk scale length, as a fraction of `galaxy_radius`.
+    /// `0.0` keeps the shape's own radial profile.
+    pub disk_scale_length: f32,
+    /// Exponential disk scale height, as a fraction of `galaxy_radius`.
+    pub disk_scale_height: f32,
+    /// Vertical spread of the bulge, as a fraction of `galaxy_radius`.
+    pub bulge_scale_height: f32,
+    pub central_black_hole: bool,
+    /// In solar masses.
+    pub black_hole_mass: f32,
+    /// [Fe/H] at the core, in dex.
+    pub core_metallicity: f32,
+    /// [Fe/H] change per `galaxy_radius` of distance from the core.
+    pub metallicity_gradient: f32,
+    pub open_cluster_count: u32,
+    pub open_cluster_stars: u32,
+    /// Plummer radius of open clusters, as a fraction of `galaxy_radius`.
+    pub open_cluster_radius: f32,
+    pub globular_cluster_count: u32,
+    pub globular_cluster_stars: u32,
+    /// Plummer radius of globular clusters, as a fraction of `galaxy_radius`.
+    pub globular_cluster_radius: f32,
+    /// Outer edge of the halo globular clusters orbit in, as a fraction of
+    /// `galaxy_radius`.
+    pub halo_radius: f32,
+    /// Rings of the polar sector grid, including the core disc.
+    pub sector_rings: u32,
+    /// Wedges in the first ring around the core, ring `k` has `k` times as many.
+    pub sector_inner_wedges: u32,
+    pub nebula_count: u32,
+    /// Typical nebula radius, as a fraction of `galaxy_radius`.
+    pub nebula_radius: f32,
+    pub lane_rule: LaneRule,
+    pub max_lane_length: f32,
+    /// Real stars to build the galaxy from instead of generating it.
+    pub catalogue: Option<StarCatalogue>,
 }
 
 impl Default for GalaxyConfig {
@@ -16,9 +64,222 @@ impl Default for GalaxyConfig {
             seed: 20250222,
             num_stars: 1000,
             galaxy_radius: 1500.0,
-            arm_strength: 1.0,
-            arm_count: 5,
             noise_scale: 1.0,
+            shape: GalaxyShape::default(),
+            min_star_separation: 40.0,
+            placement_attempts: 30,
+            bulge_fraction: 0.15,
+            bulge_radius: 0.15,
+            disk_scale_length: 4.0,
+            disk_scale_height: 0.05,
+            bulge_scale_height: 0.12,
+            central_black_hole: true,
+            black_hole_mass: 4.3e6,
+            core_metallicity: 0.3,
+            metallicity_gradient: -0.1,
+            open_cluster_count: 8,
+            open_cluster_stars: 15,
+            open_cluster_radius: 0.04,
+            globular_cluster_count: 4,
+            globular_cluster_stars: 30,
+            globular_cluster_radius: 0.06,
+            halo_radius: 3.0,
+            sector_rings: 5,
+            sector_inner_wedges: 4,
+            nebula_count: 24,
+            nebula_radius: 0.15,
+            lane_rule: LaneRule::default(),
+            max_lane_length: 2000.0,
+            catalogue: None,
+        }
+    }
+}
+
+impl GalaxyConfig {
+    /// Generator for everything derived from the galaxy seed.
+    pub fn rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.seed as u64)
+    }
+
+    /// Generator for what happens to one star at one `step`, the same
+    /// whatever order the stars are visited in and on every build.
+    pub fn event_rng(&self, star: &ID, step: u64) -> StdRng {
+        let mut state = split_mix(self.seed as u64);
+        for chunk in star.0.as_bytes().chunks(8) {
+            let mut bytes = [0; 8];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            state = split_mix(state ^ u64::from_le_bytes(bytes));
+        }
+        StdRng::seed_from_u64(split_mix(state ^ step))
+    }
+}
+
+/// SplitMix64 finalizer, scrambles `x` into a well spread value.
+fn split_mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Galaxy morphology. Lengths are fractions of `GalaxyConfig::galaxy_radius`,
+/// `*_fraction` fields are shares of `GalaxyConfig::num_stars`.
+#[derive(Debug, Clone)]
+pub enum GalaxyShape {
+    /// Logarithmic spiral arms.
+    Spiral { arm_count: u32, arm_strength: f32 },
+    /// Central bar with spiral arms starting at its ends.
+    BarredSpiral {
+        arm_count: u32,
+        arm_strength: f32,
+        bar_length: f32,
+        bar_width: f32,
+        bar_fraction: f32,
+    },
+    /// Smooth Hernquist profile squashed along one axis.
+    Elliptical {
+        scale_radius: f32,
+        axis_ratio: f32,
+        rotation: f32,
+    },
+    /// Dense core surrounded by a gaussian ring.
+    Ring {
+        ring_radius: f32,
+        ring_width: f32,
+        core_radius: f32,
+        core_fraction: f32,
+    },
+    /// Noise-warped clumps with no global structure.
+    Irregular {
+        clump_count: u32,
+        clump_spread: f32,
+        warp_strength: f32,
+    },
+    /// Plummer clusters over a sparse uniform field.
+    Clustered {
+        cluster_count: u32,
+        cluster_radius: f32,
+        field_fraction: f32,
+    },
+}
+
+impl Default for GalaxyShape {
+    fn default() -> Self {
+        Self::Spiral {
+            arm_count: 5,
+            arm_strength: 1.0,
+        }
+    }
+}
+
+impl GalaxyShape {
+    /// One default-parameter instance of every shape.
+    pub fn presets() -> [Self; 6] {
+        [
+            Self::default(),
+            Self::barred_spiral(),
+            Self::elliptical(),
+            Self::ring(),
+            Self::irregular(),
+            Self::clustered(),
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Spiral { .. } => "Spiral",
+            Self::BarredSpiral { .. } => "Barred spiral",
+            Self::Elliptical { .. } => "Elliptical",
+            Self::Ring { .. } => "Ring",
+            Self::Irregular { .. } => "Irregular",
+            Self::Clustered { .. } => "Clustered",
+        }
+    }
+
+    pub fn barred_spiral() -> Self {
+        Self::BarredSpiral {
+            arm_count: 2,
+            arm_strength: 1.0,
+            bar_length: 0.5,
+            bar_width: 0.04,
+            bar_fraction: 0.25,
+        }
+    }
+
+    pub fn elliptical() -> Self {
+        Self::Elliptical {
+            scale_radius: 0.2,
+            axis_ratio: 0.6,
+            rotation: 0.4,
+        }
+    }
+
+    pub fn ring() -> Self {
+        Self::Ring {
+            ring_radius: 0.7,
+            ring_width: 0.06,
+            core_radius: 0.1,
+            core_fraction: 0.3,
+        }
+    }
+
+    pub fn irregular() -> Self {
+        Self::Irregular {
+            clump_count: 6,
+            clump_spread: 0.12,
+            warp_strength: 0.15,
+        }
+    }
+
+    pub fn clustered() -> Self {
+        Self::Clustered {
+            cluster_count: 12,
+            cluster_radius: 0.03,
+            field_fraction: 0.2,
+        }
+    }
+}
+
+/// Rule deciding which pairs of stars closer than
+/// `GalaxyConfig::max_lane_length` get a hyperlane.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LaneRule {
+    /// Every pair within range is connected.
+    Proximity,
+    /// A pair is connected unless a third star is closer to both of them
+    /// than they are to each other.
+    #[default]
+    RelativeNeighbourhood,
+}
+
+impl LaneRule {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Proximity => "Proximity",
+            Self::RelativeNeighbourhood => "Relative neighbourhood",
+        }
+    }
+}
+
+/// CSV star catalogue, with paths relative to the assets folder.
+#[derive(Debug, Clone)]
+pub struct StarCatalogue {
+    /// `name,designation,x_pc,y_pc,z_pc,spectral_type,luminosity_class,abs_magnitude`
+    /// in galactic coordinates.
+    pub stars: PathBuf,
+    /// `host,name,mass_earth,semi_major_axis_au`, with the host's designation.
+    pub planets: PathBuf,
+    /// Map units per parsec.
+    pub scale: f32,
+}
+
+impl StarCatalogue {
+    /// Stars within a dozen parsecs of the Sun.
+    pub fn local_neighbourhood() -> Self {
+        Self {
+            stars: "catalogues/local_neighbourhood.csv".into(),
+            planets: "catalogues/local_planets.csv".into(),
+            scale: 100.0,
         }
     }
 }
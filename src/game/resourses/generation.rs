@@ -0,0 +1,72 @@
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+use bevy::{
+    prelude::Resource,
+    tasks::{AsyncComputeTaskPool, Task},
+};
+
+use crate::game::systems::galaxy_generation::{generate_galaxy, GeneratedGalaxy};
+
+use super::{galaxy::GalaxyConfig, naming::NamingConfig};
+
+/// Counters shared between the generation task and the loading screen.
+#[derive(Default)]
+pub struct GenerationProgress {
+    pub total_stars: AtomicUsize,
+    pub stars_placed: AtomicUsize,
+    pub systems_generated: AtomicUsize,
+    /// Stars whose hyperlane neighbours have been searched.
+    pub stars_linked: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl GenerationProgress {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub fn star_placed(&self) {
+        self.stars_placed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn system_generated(&self) {
+        self.systems_generated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn star_linked(&self) {
+        self.stars_linked.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Galaxy being generated in the background.
+#[derive(Resource, Default)]
+pub struct GalaxyGeneration {
+    pub task: Option<Task<Option<GeneratedGalaxy>>>,
+    pub progress: Arc<GenerationProgress>,
+}
+
+impl GalaxyGeneration {
+    pub fn start(&mut self, config: GalaxyConfig, naming: NamingConfig) {
+        self.cancel();
+
+        let progress = Arc::new(GenerationProgress::default());
+        progress
+            .total_stars
+            .store(config.num_stars, Ordering::Relaxed);
+        self.progress = progress.clone();
+
+        self.task = Some(
+            AsyncComputeTaskPool::get()
+                .spawn(async move { generate_galaxy(&config, &naming, &progress) }),
+        );
+    }
+
+    /// Tells the running task to stop at its next check and forgets it.
+    pub fn cancel(&mut self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+        self.task = None;
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+use bevy::prelude::Resource;
+use vecmath::{vec3_len, vec3_sub, Vector3};
+
+use crate::game::components::common::ID;
+
+/// Jump lanes between star systems.
+#[derive(Resource, Default)]
+pub struct HyperlaneGraph {
+    nodes: Vec<ID>,
+    positions: Vec<Vector3<f32>>,
+    index: HashMap<ID, usize>,
+    adjacency: Vec<Vec<(usize, f32)>>,
+    lanes: Vec<(usize, usize)>,
+}
+
+impl HyperlaneGraph {
+    pub fn new(nodes: Vec<(ID, Vector3<f32>)>, lanes: Vec<(usize, usize)>) -> Self {
+        let (nodes, positions): (Vec<_>, Vec<_>) = nodes.into_iter().unzip();
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), i))
+            .collect();
+
+        let mut adjacency = vec![Vec::new(); nodes.len()];
+        for &(a, b) in lanes.iter() {
+            let length = vec3_len(vec3_sub(positions[a], positions[b]));
+            adjacency[a].push((b, length));
+            adjacency[b].push((a, length));
+        }
+
+        Self {
+            nodes,
+            positions,
+            index,
+            adjacency,
+            lanes,
+        }
+    }
+
+    /// End points of every lane.
+    pub fn lanes(&self) -> impl Iterator<Item = (Vector3<f32>, Vector3<f32>)> + '_ {
+        self.lanes
+            .iter()
+            .map(|&(a, b)| (self.positions[a], self.positions[b]))
+    }
+
+    pub fn position(&self, id: &ID) -> Option<Vector3<f32>> {
+        self.index.get(id).map(|&i| self.positions[i])
+    }
+
+    /// Systems one jump away from `id`.
+    pub fn neighbours(&self, id: &ID) -> Vec<&ID> {
+        self.index
+            .get(id)
+            .map(|&i| {
+                self.adjacency[i]
+                    .iter()
+                    .map(|&(j, _)| &self.nodes[j])
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Shortest route by total lane length, including both ends, together
+    /// with its length. `None` if the systems are not connected.
+    pub fn shortest_path(&self, from: &ID, to: &ID) -> Option<(Vec<ID>, f32)> {
+        let start = *self.index.get(from)?;
+        let goal = *self.index.get(to)?;
+
+        let mut distances = vec![f32::INFINITY; self.nodes.len()];
+        let mut previous = vec![None; self.nodes.len()];
+        let mut queue = BinaryHeap::new();
+
+        distances[start] = 0.0;
+        queue.push(Visit {
+            node: start,
+            distance: 0.0,
+        });
+
+        while let Some(Visit { node, distance }) = queue.pop() {
+            if node == goal {
+                break;
+            }
+            if distance > distances[node] {
+                continue;
+            }
+            for &(next, length) in self.adjacency[node].iter() {
+                let candidate = distance + length;
+                if candidate < distances[next] {
+                    distances[next] = candidate;
+                    previous[next] = Some(node);
+                    queue.push(Visit {
+                        node: next,
+                        distance: candidate,
+                    });
+                }
+            }
+        }
+
+        if distances[goal].is_infinite() {
+            return None;
+        }
+
+        let mut path = vec![self.nodes[goal].clone()];
+        let mut current = goal;
+        while let Some(node) = previous[current] {
+            path.push(self.nodes[node].clone());
+            current = node;
+        }
+        path.reverse();
+
+        Some((path, distances[goal]))
+    }
+}
+
+/// Dijkstra queue entry, ordered so that `BinaryHeap` pops the closest node.
+struct Visit {
+    node: usize,
+    distance: f32,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance == other.distance
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
@@ -1,2 +1,9 @@
+pub mod evolution;
 pub mod galaxy;
+pub mod generation;
+pub mod hyperlanes;
+pub mod naming;
+pub mod sectors;
 pub mod system;
+pub mod time;
+pub mod variability;
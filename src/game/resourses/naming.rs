@@ -0,0 +1,76 @@
+use bevy::prelude::Resource;
+
+#[derive(Resource, Clone)]
+pub struct NamingConfig {
+    /// Share of stars that get a proper name, the rest are known by their
+    /// catalogue designation.
+    pub proper_name_fraction: f32,
+    /// Cultures split the galaxy into angular wedges, one wedge each.
+    pub cultures: Vec<NameCulture>,
+}
+
+/// Syllable table for proper names. A name is `min_syllables..=max_syllables`
+/// syllables followed by one ending.
+#[derive(Clone)]
+pub struct NameCulture {
+    pub syllables: Vec<String>,
+    pub endings: Vec<String>,
+    pub min_syllables: usize,
+    pub max_syllables: usize,
+}
+
+impl NameCulture {
+    pub fn new(
+        syllables: &[&str],
+        endings: &[&str],
+        min_syllables: usize,
+        max_syllables: usize,
+    ) -> Self {
+        Self {
+            syllables: syllables.iter().map(|s| s.to_string()).collect(),
+            endings: endings.iter().map(|s| s.to_string()).collect(),
+            min_syllables,
+            max_syllables,
+        }
+    }
+}
+
+impl Default for NamingConfig {
+    fn default() -> Self {
+        Self {
+            proper_name_fraction: 0.3,
+            cultures: vec![
+                // Classical.
+                NameCulture::new(
+                    &[
+                        "al", "ca", "de", "ne", "ri", "sa", "to", "ve", "mi", "lu", "por", "can",
+                        "bel", "tar", "ae", "sir", "rig", "an",
+                    ],
+                    &["us", "a", "is", "on", "ix", "um", "ar", "ion"],
+                    1,
+                    2,
+                ),
+                // Sora.
+                NameCulture::new(
+                    &[
+                        "ka", "ki", "ko", "sa", "shi", "su", "ta", "chi", "to", "na", "ha", "ho",
+                        "mi", "mo", "ya", "yu", "ra", "ri", "wa", "ze",
+                    ],
+                    &["", "n", "ra", "ko", "ri", "boshi"],
+                    2,
+                    3,
+                ),
+                // Northern.
+                NameCulture::new(
+                    &[
+                        "sk", "vald", "thor", "fjel", "ulf", "ing", "hal", "brek", "sig", "ran",
+                        "dag", "eir", "hrim", "ask",
+                    ],
+                    &["heim", "gard", "a", "ir", "und", "vik", "ra"],
+                    1,
+                    2,
+                ),
+            ],
+        }
+    }
+}
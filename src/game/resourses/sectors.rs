@@ -0,0 +1,66 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::Resource;
+use vecmath::Vector2;
+
+/// Polar grid of named sectors. Ring 0 is a single core disc, ring `k` is
+/// split into `k * inner_wedges` wedges so cells stay roughly the same size.
+#[derive(Resource, Default)]
+pub struct GalaxySectors {
+    pub ring_width: f32,
+    pub inner_wedges: u32,
+    pub sectors: Vec<Sector>,
+}
+
+#[derive(Clone)]
+pub struct Sector {
+    pub name: String,
+    /// Short code used in catalogue designations.
+    pub code: String,
+    pub ring: u32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    pub start_angle: f32,
+    pub end_angle: f32,
+}
+
+impl Sector {
+    /// Point in the middle of the cell, where its label goes.
+    pub fn label_position(&self) -> Vector2<f32> {
+        if self.ring == 0 {
+            return [0.0, 0.0];
+        }
+        let r = 0.5 * (self.inner_radius + self.outer_radius);
+        let angle = 0.5 * (self.start_angle + self.end_angle);
+        [r * angle.cos(), r * angle.sin()]
+    }
+}
+
+impl GalaxySectors {
+    pub fn wedges_in_ring(&self, ring: u32) -> u32 {
+        if ring == 0 {
+            1
+        } else {
+            ring * self.inner_wedges.max(1)
+        }
+    }
+
+    /// Index into `sectors` of the cell containing `position`. Points beyond
+    /// the outer ring fall into its cells.
+    pub fn sector_at(&self, position: Vector2<f32>) -> Option<usize> {
+        if self.sectors.is_empty() || self.ring_width <= 0.0 {
+            return None;
+        }
+
+        let rings = self.sectors.last()?.ring + 1;
+        let r = (position[0].powi(2) + position[1].powi(2)).sqrt();
+        let ring = ((r / self.ring_width) as u32).min(rings - 1);
+
+        let first = (0..ring).map(|k| self.wedges_in_ring(k)).sum::<u32>();
+        let wedges = self.wedges_in_ring(ring);
+        let angle = position[1].atan2(position[0]).rem_euclid(TAU);
+        let wedge = ((angle / TAU * wedges as f32) as u32).min(wedges - 1);
+
+        Some((first + wedge) as usize)
+    }
+}
@@ -2,13 +2,73 @@ use bevy::prelude::Resource;
 
 use crate::game::components::common::ID;
 
-#[derive(Resource)]
+#[derive(Resource, Clone)]
 pub struct SystemGenConfig {
+    /// Planets plus the moons made of what does not fit as a planet.
     pub min_bodies: u32,
     pub max_bodies: u32,
     pub log_mean_mass: f64,
     pub log_std_mass: f64,
     pub roche_limit_factor: f64,
+    /// Body count scales by `10^(factor * [Fe/H])` of the host star.
+    pub metallicity_body_factor: f64,
+    /// Core masses scale by `10^(factor * [Fe/H])` of the host star.
+    pub metallicity_mass_factor: f64,
+    /// Cores past this many Earth masses pull in a gas envelope and become
+    /// giants (Pollack et al. 1996).
+    pub giant_core_mass: f64,
+    /// Giant masses are log-uniform between these, in Earth masses.
+    pub min_giant_mass: f64,
+    pub max_giant_mass: f64,
+    /// Orbits inside this many AU were swallowed by the giant phase of a
+    /// white dwarf's progenitor.
+    pub white_dwarf_engulfment_radius: f64,
+    /// Chance of a remnant keeping a debris disk from what it destroyed.
+    pub debris_disk_chance: f64,
+    /// Chance of a pulsar having formed planets from supernova fallback.
+    pub pulsar_planet_chance: f64,
+    /// Body masses beyond the host's frost line scale by this much.
+    pub frost_line_mass_factor: f64,
+    /// Chance of a gas giant drawn inside the frost line staying there, as if
+    /// it had migrated in. The others move out past the line.
+    pub inner_giant_chance: f64,
+    /// Planet eccentricities follow a Beta distribution with these shapes,
+    /// the fit to radial-velocity planets (Kipping 2013).
+    pub eccentricity_alpha: f64,
+    pub eccentricity_beta: f64,
+    /// Moons, circularised by tides, get this fraction of a planet's
+    /// eccentricity.
+    pub moon_eccentricity_factor: f64,
+    /// Rayleigh scale of orbital inclinations, in degrees.
+    pub inclination_scale: f64,
+    /// Chance of a belt between the 4:1 and 2:1 resonances of the innermost
+    /// giant, or just past the frost line when there is no giant.
+    pub inner_belt_chance: f64,
+    /// Chance of a belt between the 3:2 and 2:1 resonances outside the
+    /// outermost giant.
+    pub outer_belt_chance: f64,
+    /// Belt masses are log-uniform between these, in kg.
+    pub min_belt_mass: f64,
+    pub max_belt_mass: f64,
+    /// Chance of a gas giant having a ring system.
+    pub ring_chance: f64,
+    /// Innermost planet orbit range, in AU around a solar-mass star.
+    pub min_inner_orbit: f64,
+    pub max_inner_orbit: f64,
+    /// No planets beyond this many AU around a solar-mass star.
+    pub outer_orbit: f64,
+    /// Orbit distances scale by the star's mass to this power.
+    pub extent_mass_exponent: f64,
+    /// Period ratios between neighbouring planets are log-uniform between
+    /// these.
+    pub min_period_ratio: f64,
+    pub max_period_ratio: f64,
+    /// Least gap between neighbouring orbits, in mutual Hill radii. Ten or
+    /// more keeps systems stable over billions of years (Chambers et al.
+    /// 1996).
+    pub min_hill_spacing: f64,
+    /// Moons orbit within this fraction of their planet's Hill radius.
+    pub moon_hill_fraction: f64,
 }
 
 impl Default for SystemGenConfig {
@@ -19,6 +79,33 @@ impl Default for SystemGenConfig {
             log_mean_mass: 0.1,
             log_std_mass: 0.5,
             roche_limit_factor: 1.2,
+            metallicity_body_factor: 0.5,
+            metallicity_mass_factor: 0.3,
+            giant_core_mass: 5.0,
+            min_giant_mass: 35.0,
+            max_giant_mass: 3000.0,
+            white_dwarf_engulfment_radius: 2.0,
+            debris_disk_chance: 0.4,
+            pulsar_planet_chance: 0.3,
+            frost_line_mass_factor: 3.0,
+            inner_giant_chance: 0.1,
+            eccentricity_alpha: 0.867,
+            eccentricity_beta: 3.03,
+            moon_eccentricity_factor: 0.2,
+            inclination_scale: 2.0,
+            inner_belt_chance: 0.6,
+            outer_belt_chance: 0.5,
+            min_belt_mass: 1e20,
+            max_belt_mass: 1e23,
+            ring_chance: 0.5,
+            min_inner_orbit: 0.03,
+            max_inner_orbit: 0.5,
+            outer_orbit: 60.0,
+            extent_mass_exponent: 1.0,
+            min_period_ratio: 1.3,
+            max_period_ratio: 3.0,
+            min_hill_spacing: 10.0,
+            moon_hill_fraction: 0.4,
         }
     }
 }
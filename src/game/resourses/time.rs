@@ -0,0 +1,33 @@
+use bevy::prelude::Resource;
+
+/// In-game clock, in years since the galaxy was generated.
+#[derive(Resource)]
+pub struct GameTime {
+    pub years: f64,
+    /// Years per real second, `0.0` when paused.
+    pub speed: f64,
+    /// Years elapsed during the current frame.
+    pub delta: f64,
+}
+
+impl Default for GameTime {
+    fn default() -> Self {
+        Self {
+            years: 0.0,
+            speed: 1.0,
+            delta: 0.0,
+        }
+    }
+}
+
+impl GameTime {
+    /// Speeds offered by the time controls.
+    pub const SPEEDS: [(&'static str, f64); 6] = [
+        ("Pause", 0.0),
+        ("1 yr/s", 1.0),
+        ("1 kyr/s", 1e3),
+        ("1 Myr/s", 1e6),
+        ("100 Myr/s", 1e8),
+        ("1 Gyr/s", 1e9),
+    ];
+}
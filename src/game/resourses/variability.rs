@@ -0,0 +1,20 @@
+use bevy::prelude::Resource;
+
+#[derive(Resource, Clone)]
+pub struct VariabilityConfig {
+    /// How long a flare keeps its star brighter, in years. Each star may
+    /// flare once per such step of game time.
+    pub flare_duration: f64,
+    /// Integrity an exposed structure at 1 AU loses to a flare doubling its
+    /// star's output. Falls off with the square of distance.
+    pub flare_damage: f32,
+}
+
+impl Default for VariabilityConfig {
+    fn default() -> Self {
+        Self {
+            flare_duration: 0.02,
+            flare_damage: 0.1,
+        }
+    }
+}
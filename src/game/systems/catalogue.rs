@@ -0,0 +1,220 @@
+use std::{collections::HashMap, fs, path::Path, sync::atomic::Ordering};
+
+use bevy::{asset::io::file::FileAssetReader, log::warn};
+use rand::Rng;
+use vecmath::Vector3;
+
+use crate::game::{
+    components::{
+        common::SystemSeed,
+        orbit::AU,
+        planet::EARTH_MASS,
+        spatial::planar,
+        star::{SectorId, SpectralDesignation, Star, StellarPopulation},
+    },
+    resourses::{
+        galaxy::{GalaxyConfig, StarCatalogue},
+        generation::GenerationProgress,
+        naming::NamingConfig,
+    },
+};
+
+use super::{
+    galaxy_generation::{generate_sectors, GeneratedGalaxy, GeneratedStar, StarPlacement},
+    hyperlanes::build_hyperlanes,
+    naming::NameGenerator,
+    star_system_generation::{
+        known_planet, set_environments, set_equilibrium_temperatures, set_orbit_periods,
+    },
+};
+
+/// Row of a star catalogue.
+pub struct CatalogueStar {
+    pub name: String,
+    pub designation: String,
+    /// In parsecs.
+    pub position: Vector3<f32>,
+    pub spectral: SpectralDesignation,
+    pub abs_magnitude: f32,
+}
+
+/// Row of a planet catalogue.
+pub struct CataloguePlanet {
+    /// Designation of the host star.
+    pub host: String,
+    /// In Earth masses.
+    pub mass: f32,
+    /// In AU.
+    pub semi_major_axis: f32,
+}
+
+/// Stars of a catalogue file, skipping the header and any row that does not
+/// parse.
+pub fn parse_stars(text: &str) -> Vec<CatalogueStar> {
+    rows(text)
+        .filter_map(|(line, fields)| {
+            let star = parse_star(&fields);
+            if star.is_none() {
+                warn!(
+                    "Skipping star catalogue line {}: {}",
+                    line,
+                    fields.join(",")
+                );
+            }
+            star
+        })
+        .collect()
+}
+
+fn parse_star(fields: &[&str]) -> Option<CatalogueStar> {
+    let [name, designation, x, y, z, spectral_type, luminosity_class, abs_magnitude] = fields
+    else {
+        return None;
+    };
+    let spectral = SpectralDesignation {
+        star_class: luminosity_class.parse().ok()?,
+        ..spectral_type.parse().ok()?
+    };
+
+    Some(CatalogueStar {
+        name: name.to_string(),
+        designation: designation.to_string(),
+        position: [x.parse().ok()?, y.parse().ok()?, z.parse().ok()?],
+        spectral,
+        abs_magnitude: abs_magnitude.parse().ok()?,
+    })
+}
+
+pub fn parse_planets(text: &str) -> Vec<CataloguePlanet> {
+    rows(text)
+        .filter_map(|(line, fields)| {
+            let planet = match fields[..] {
+                [host, _, mass, semi_major_axis] => Some(CataloguePlanet {
+                    host: host.to_string(),
+                    mass: mass.parse().ok()?,
+                    semi_major_axis: semi_major_axis.parse().ok()?,
+                })
+                .filter(|p| p.mass > 0.0 && p.semi_major_axis > 0.0),
+                _ => None,
+            };
+            if planet.is_none() {
+                warn!(
+                    "Skipping planet catalogue line {}: {}",
+                    line,
+                    fields.join(",")
+                );
+            }
+            planet
+        })
+        .collect()
+}
+
+/// Trimmed fields of every non-empty line after the header, with 1-based
+/// line numbers.
+fn rows(text: &str) -> impl Iterator<Item = (usize, Vec<&str>)> {
+    text.lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| (i + 1, line.split(',').map(str::trim).collect()))
+}
+
+fn read_asset(path: &Path) -> Option<String> {
+    let path = FileAssetReader::get_base_path().join("assets").join(path);
+    fs::read_to_string(&path)
+        .inspect_err(|e| warn!("Cannot read catalogue {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Galaxy made of the catalogue's stars. Known planets are placed as
+/// catalogued, every other system gets the usual generated planets.
+pub fn generate_catalogue_galaxy(
+    config: &GalaxyConfig,
+    catalogue: &StarCatalogue,
+    naming: &NamingConfig,
+    progress: &GenerationProgress,
+) -> Option<GeneratedGalaxy> {
+    let entries = parse_stars(&read_asset(&catalogue.stars)?);
+    let mut planets = HashMap::<String, Vec<CataloguePlanet>>::new();
+    for planet in parse_planets(&read_asset(&catalogue.planets).unwrap_or_default()) {
+        planets.entry(planet.host.clone()).or_default().push(planet);
+    }
+    progress.total_stars.store(entries.len(), Ordering::Relaxed);
+
+    let mut rng = config.rng();
+    let placements = entries
+        .iter()
+        .map(|entry| StarPlacement {
+            position: entry.position.map(|c| c * catalogue.scale),
+            population: StellarPopulation::Disk,
+            cluster: None,
+        })
+        .inspect(|_| progress.star_placed())
+        .collect::<Vec<_>>();
+
+    let mut names = NameGenerator::default();
+    let sectors = generate_sectors(config, naming, &mut rng, &mut names, &placements);
+
+    let mut stars = Vec::with_capacity(entries.len());
+    for (entry, placement) in entries.iter().zip(placements.iter()) {
+        if progress.is_cancelled() {
+            return None;
+        }
+
+        let star = Star::from_catalogue(&mut rng, entry.spectral, entry.abs_magnitude)
+            .with_name(&entry.name)
+            .with_designation(&entry.designation);
+        let seed = SystemSeed(rng.random());
+        let bodies = planets.get(&entry.designation).map(|known| {
+            let mut bodies = known
+                .iter()
+                .map(|p| {
+                    known_planet(
+                        &mut rng,
+                        &star.id,
+                        p.mass * EARTH_MASS,
+                        p.semi_major_axis as f64 * AU,
+                        star.frost_line.0,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let system_star = (&star).into();
+            set_equilibrium_temperatures(&mut bodies, &system_star, &[]);
+            set_environments(&mut bodies);
+            set_orbit_periods(&mut bodies, &system_star, &[]);
+            bodies
+        });
+        let sector = sectors
+            .sector_at(planar(placement.position))
+            .unwrap_or_default();
+
+        stars.push(GeneratedStar {
+            star,
+            position: placement.position,
+            seed,
+            sector: SectorId(sector),
+            cluster: None,
+            companions: Vec::new(),
+            bodies,
+        });
+        progress.system_generated();
+    }
+
+    let lanes = build_hyperlanes(
+        config,
+        stars
+            .iter()
+            .map(|s| (s.star.id.clone(), s.position))
+            .collect(),
+        progress,
+    )?;
+
+    Some(GeneratedGalaxy {
+        stars,
+        clusters: Vec::new(),
+        black_hole: None,
+        nebulae: Vec::new(),
+        sectors,
+        lanes,
+    })
+}
@@ -1,48 +1,867 @@
-use bevy::prelude::{Commands, Res};
+use bevy::{
+    log::warn,
+    prelude::{BuildChildren, ChildBuild, Commands, Event, EventWriter, ResMut},
+    tasks::{block_on, poll_once},
+};
 use core::f32;
-use noise::{NoiseFn, Perlin};
+use itertools::Itertools;
+use noise::{Fbm, NoiseFn, Perlin};
 use rand::Rng;
-use vecmath::Vector2;
+use rand_distr::{Distribution, StandardNormal};
+use vecmath::{vec2_len, vec3_len, vec3_sub, Vector2, Vector3};
 
 use crate::game::{
     components::{
-        common::PendingGeneration,
-        spatial::SpatialBundle,
-        star::{Star, StarBundle},
+        cluster::{
+            CClusterType, ClusterMembership, ClusterRadius, ClusterType, StarCluster,
+            StarClusterBundle,
+        },
+        common::{Mass, Name, PendingGeneration, SystemSeed, ID},
+        nebula::{CNebulaType, Nebula, NebulaBundle, NebulaShape, NebulaType},
+        planet::CelestialBodyBundle,
+        spatial::{planar, SpatialBundle},
+        star::{
+            CompanionBundle, SectorId, Star, StarBundle, StellarPopulation, SupermassiveBlackHole,
+            SupermassiveBlackHoleBundle, SystemCompanion,
+        },
+    },
+    resourses::{
+        galaxy::{GalaxyConfig, GalaxyShape},
+        generation::{GalaxyGeneration, GenerationProgress},
+        hyperlanes::HyperlaneGraph,
+        naming::{NameCulture, NamingConfig},
+        sectors::{GalaxySectors, Sector},
+        time::GameTime,
     },
-    resourses::galaxy::GalaxyConfig,
 };
+use crate::ui::{CurrentScreen, Screen};
+
+use super::{
+    catalogue::generate_catalogue_galaxy, hyperlanes::build_hyperlanes, naming::NameGenerator,
+    spatial_grid::SpatialGrid,
+};
+
+/// Where a star goes and which population it is drawn from.
+#[derive(Clone, Copy)]
+pub struct StarPlacement {
+    pub position: Vector3<f32>,
+    pub population: StellarPopulation,
+    /// Index into `GalaxyLayout::clusters`.
+    pub cluster: Option<usize>,
+}
+
+/// Draws star positions from the density profile of `GalaxyConfig::shape`.
+struct PositionSampler<'a> {
+    config: &'a GalaxyConfig,
+    perlin: Perlin,
+    /// Clump or cluster centres for shapes that need them.
+    centers: Vec<Vector2<f32>>,
+}
+
+impl<'a> PositionSampler<'a> {
+    fn new(config: &'a GalaxyConfig, rng: &mut impl Rng) -> Self {
+        let centers = match config.shape {
+            GalaxyShape::Irregular { clump_count, .. } => (0..clump_count)
+                .map(|_| uniform_disk(rng, 0.7 * config.galaxy_radius))
+                .collect(),
+            GalaxyShape::Clustered { cluster_count, .. } => (0..cluster_count)
+                .map(|_| uniform_disk(rng, config.galaxy_radius))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            config,
+            perlin: Perlin::new(config.seed),
+            centers,
+        }
+    }
+
+    /// Noise in `[-1, 1]` at a point, with the frequency normalised to the galaxy size.
+    fn noise(&self, x: f32, y: f32) -> f32 {
+        let scale = 4.0 * self.config.noise_scale / self.config.galaxy_radius;
+        self.perlin.get([(x * scale) as f64, (y * scale) as f64]) as f32
+    }
+
+    /// Bulge or disk star, with the disk thinned by the exponential profile.
+    fn sample(&self, rng: &mut impl Rng) -> StarPlacement {
+        let config = self.config;
+
+        if rng.random::<f32>() < config.bulge_fraction {
+            let b = config.bulge_radius * config.galaxy_radius;
+            let h = config.bulge_scale_height * config.galaxy_radius;
+            return StarPlacement {
+                position: [gaussian(rng) * b, gaussian(rng) * b, gaussian(rng) * h],
+                population: StellarPopulation::Bulge,
+                cluster: None,
+            };
+        }
+
+        let mut position = self.sample_shape(rng);
+        if config.disk_scale_length > 0.0 {
+            let scale = config.disk_scale_length * config.galaxy_radius;
+            // Bounded so a tiny scale length thins the disk instead of hanging.
+            for _ in 0..64 {
+                if rng.random::<f32>() < (-vec2_len(position) / scale).exp() {
+                    break;
+                }
+                position = self.sample_shape(rng);
+            }
+        }
+
+        StarPlacement {
+            position: [position[0], position[1], self.disk_height(rng)],
+            population: StellarPopulation::Disk,
+            cluster: None,
+        }
+    }
 
-pub fn generate_system_positions(config: Res<GalaxyConfig>) -> Vec<Vector2<f32>> {
-    let perlin = Perlin::new(config.seed);
-    let mut rng = rand::rng();
-    let mut positions = Vec::<Vector2<f32>>::with_capacity(config.num_stars);
+    /// Height above the plane from the exponential vertical profile.
+    fn disk_height(&self, rng: &mut impl Rng) -> f32 {
+        let h = self.config.disk_scale_height * self.config.galaxy_radius;
+        let z = -h * rng.random_range(f32::EPSILON..1.0f32).ln();
+        if rng.random() {
+            z
+        } else {
+            -z
+        }
+    }
 
-    for _ in 0..config.num_stars {
+    fn sample_shape(&self, rng: &mut impl Rng) -> Vector2<f32> {
+        match self.config.shape {
+            GalaxyShape::Spiral {
+                arm_count,
+                arm_strength,
+            } => self.spiral(rng, arm_count, arm_strength),
+            GalaxyShape::BarredSpiral {
+                arm_count,
+                arm_strength,
+                bar_length,
+                bar_width,
+                bar_fraction,
+            } => self.barred_spiral(
+                rng,
+                arm_count,
+                arm_strength,
+                bar_length,
+                bar_width,
+                bar_fraction,
+            ),
+            GalaxyShape::Elliptical {
+                scale_radius,
+                axis_ratio,
+                rotation,
+            } => self.elliptical(rng, scale_radius, axis_ratio, rotation),
+            GalaxyShape::Ring {
+                ring_radius,
+                ring_width,
+                core_radius,
+                core_fraction,
+            } => self.ring(rng, ring_radius, ring_width, core_radius, core_fraction),
+            GalaxyShape::Irregular {
+                clump_spread,
+                warp_strength,
+                ..
+            } => self.irregular(rng, clump_spread, warp_strength),
+            GalaxyShape::Clustered {
+                cluster_radius,
+                field_fraction,
+                ..
+            } => self.clustered(rng, cluster_radius, field_fraction),
+        }
+    }
+
+    fn spiral(&self, rng: &mut impl Rng, arm_count: u32, arm_strength: f32) -> Vector2<f32> {
+        let config = self.config;
         let base_angle = rng.random_range(0.0..2.0 * std::f32::consts::PI);
 
         let r = config.galaxy_radius * f32::consts::E.powf(base_angle * 0.5);
-        let spiral_spread = config.arm_strength * (r + 1.0).ln();
+        let spiral_spread = arm_strength * (r + 1.0).ln();
 
-        let spiral_offset = f32::consts::TAU / config.arm_count as f32
-            * rng.random_range(0..=config.arm_count) as f32;
+        let arm = rng.random_range(0..arm_count.max(1));
+        let spiral_offset = f32::consts::TAU / arm_count.max(1) as f32 * arm as f32;
 
         let nx = config.noise_scale * r * base_angle.cos();
         let ny = config.noise_scale * r * base_angle.sin();
-        let noise_val = perlin.get([nx as f64, ny as f64]) as f32;
+        let noise_val = self.perlin.get([nx as f64, ny as f64]) as f32;
 
         let r_final = r * (1.0 + 0.5 * noise_val) + spiral_spread;
         let angle_final = base_angle + 0.5 * noise_val + spiral_offset;
 
-        positions.push([r_final * angle_final.cos(), r_final * angle_final.sin()]);
+        [r_final * angle_final.cos(), r_final * angle_final.sin()]
+    }
+
+    fn barred_spiral(
+        &self,
+        rng: &mut impl Rng,
+        arm_count: u32,
+        arm_strength: f32,
+        bar_length: f32,
+        bar_width: f32,
+        bar_fraction: f32,
+    ) -> Vector2<f32> {
+        let radius = self.config.galaxy_radius;
+        let bar_end = 0.5 * bar_length * radius;
+
+        if rng.random::<f32>() < bar_fraction {
+            let along = rng.random_range(-bar_end..=bar_end);
+            let across = gaussian(rng) * bar_width * radius;
+            return [along, across];
+        }
+
+        // Each arm makes one turn from a bar end out to the galaxy edge.
+        let t = rng.random::<f32>();
+        let r = bar_end * (radius / bar_end).powf(t);
+        let arm = rng.random_range(0..arm_count.max(1));
+        let angle = t * f32::consts::TAU + f32::consts::TAU / arm_count.max(1) as f32 * arm as f32;
+
+        let noise_val = self.noise(r * angle.cos(), r * angle.sin());
+        let spread = gaussian(rng) * arm_strength * 0.05 * radius;
+
+        let r_final = r * (1.0 + 0.2 * noise_val) + spread;
+        let angle_final = angle + 0.3 * noise_val;
+        [r_final * angle_final.cos(), r_final * angle_final.sin()]
+    }
+
+    fn elliptical(
+        &self,
+        rng: &mut impl Rng,
+        scale_radius: f32,
+        axis_ratio: f32,
+        rotation: f32,
+    ) -> Vector2<f32> {
+        let radius = self.config.galaxy_radius;
+        let a = scale_radius * radius;
+
+        // Inverse CDF of the Hernquist profile, truncated at the galaxy edge.
+        let r = loop {
+            let s = rng.random::<f32>().sqrt();
+            let r = a * s / (1.0 - s);
+            if r <= radius {
+                break r;
+            }
+        };
+        let angle = rng.random_range(0.0..f32::consts::TAU);
+        let r = r * (1.0 + 0.1 * self.noise(r * angle.cos(), r * angle.sin()));
+
+        let x = r * angle.cos();
+        let y = r * angle.sin() * axis_ratio;
+        let (sin, cos) = rotation.sin_cos();
+        [x * cos - y * sin, x * sin + y * cos]
+    }
+
+    fn ring(
+        &self,
+        rng: &mut impl Rng,
+        ring_radius: f32,
+        ring_width: f32,
+        core_radius: f32,
+        core_fraction: f32,
+    ) -> Vector2<f32> {
+        let radius = self.config.galaxy_radius;
+
+        if rng.random::<f32>() < core_fraction {
+            return [
+                gaussian(rng) * core_radius * radius,
+                gaussian(rng) * core_radius * radius,
+            ];
+        }
+
+        let angle = rng.random_range(0.0..f32::consts::TAU);
+        let r = (ring_radius * radius + gaussian(rng) * ring_width * radius).abs();
+        let r = r * (1.0 + 0.1 * self.noise(r * angle.cos(), r * angle.sin()));
+        [r * angle.cos(), r * angle.sin()]
+    }
+
+    fn irregular(&self, rng: &mut impl Rng, clump_spread: f32, warp_strength: f32) -> Vector2<f32> {
+        let radius = self.config.galaxy_radius;
+        let center = self.pick_center(rng);
+
+        let x = center[0] + gaussian(rng) * clump_spread * radius;
+        let y = center[1] + gaussian(rng) * clump_spread * radius;
+
+        // Domain warp so the clumps lose their round outlines.
+        let warp = warp_strength * radius;
+        [
+            x + warp * self.noise(x, y),
+            y + warp * self.noise(x + 0.31 * radius, y + 0.17 * radius),
+        ]
+    }
+
+    fn clustered(
+        &self,
+        rng: &mut impl Rng,
+        cluster_radius: f32,
+        field_fraction: f32,
+    ) -> Vector2<f32> {
+        let radius = self.config.galaxy_radius;
+
+        if self.centers.is_empty() || rng.random::<f32>() < field_fraction {
+            return uniform_disk(rng, radius);
+        }
+
+        // Seen from above, so only the in-plane part of the offset counts.
+        let center = self.pick_center(rng);
+        let offset = plummer_offset(rng, cluster_radius * radius);
+        [center[0] + offset[0], center[1] + offset[1]]
+    }
+
+    fn pick_center(&self, rng: &mut impl Rng) -> Vector2<f32> {
+        if self.centers.is_empty() {
+            return [0.0, 0.0];
+        }
+        self.centers[rng.random_range(0..self.centers.len())]
+    }
+}
+
+fn gaussian(rng: &mut impl Rng) -> f32 {
+    StandardNormal.sample(rng)
+}
+
+/// Offset drawn from the Plummer sphere with scale radius `b`, cut at five
+/// scale radii.
+fn plummer_offset(rng: &mut impl Rng, b: f32) -> Vector3<f32> {
+    let r = loop {
+        let u = rng.random_range(f32::EPSILON..1.0f32);
+        let r = b / (u.powf(-2.0 / 3.0) - 1.0).sqrt();
+        if r <= 5.0 * b {
+            break r;
+        }
+    };
+    uniform_direction(rng, r)
+}
+
+/// Point at distance `r` in a uniformly random direction.
+fn uniform_direction(rng: &mut impl Rng, r: f32) -> Vector3<f32> {
+    let cos_theta = rng.random_range(-1.0..=1.0f32);
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let angle = rng.random_range(0.0..f32::consts::TAU);
+    [
+        r * sin_theta * angle.cos(),
+        r * sin_theta * angle.sin(),
+        r * cos_theta,
+    ]
+}
+
+fn uniform_disk(rng: &mut impl Rng, radius: f32) -> Vector2<f32> {
+    let r = radius * rng.random::<f32>().sqrt();
+    let angle = rng.random_range(0.0..f32::consts::TAU);
+    [r * angle.cos(), r * angle.sin()]
+}
+
+/// [Fe/H] falling off linearly with distance from the core, with some scatter.
+/// Globular clusters formed before the disk was enriched and stay metal-poor.
+fn metallicity_at(config: &GalaxyConfig, rng: &mut impl Rng, placement: &StarPlacement) -> f32 {
+    let metallicity = match placement.population {
+        StellarPopulation::Globular => -1.5 + 0.2 * gaussian(rng),
+        _ => {
+            let r = vec2_len(planar(placement.position)) / config.galaxy_radius;
+            config.core_metallicity + config.metallicity_gradient * r + 0.1 * gaussian(rng)
+        }
+    };
+    metallicity.clamp(-2.5, 0.6)
+}
+
+/// Cluster about to be filled with stars.
+pub struct ClusterPlacement {
+    pub cluster_type: ClusterType,
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+pub struct GalaxyLayout {
+    pub stars: Vec<StarPlacement>,
+    pub clusters: Vec<ClusterPlacement>,
+}
+
+/// Dart throwing: candidates closer than `GalaxyConfig::min_star_separation`
+/// to an accepted star are thrown away, the rest keep the density they were
+/// drawn with.
+struct Placer<'a> {
+    config: &'a GalaxyConfig,
+    progress: &'a GenerationProgress,
+    stars: Vec<StarPlacement>,
+    grid: SpatialGrid,
+}
+
+impl<'a> Placer<'a> {
+    fn new(config: &'a GalaxyConfig, progress: &'a GenerationProgress) -> Self {
+        Self {
+            config,
+            progress,
+            stars: Vec::with_capacity(config.num_stars),
+            grid: SpatialGrid::new(config.min_star_separation),
+        }
+    }
+
+    /// Draws up to `placement_attempts` candidates per wanted star and
+    /// returns how many were accepted.
+    fn place<R: Rng>(
+        &mut self,
+        rng: &mut R,
+        wanted: usize,
+        mut candidate: impl FnMut(&mut R) -> StarPlacement,
+    ) -> usize {
+        let separation = self.config.min_star_separation;
+        let max_candidates = wanted * self.config.placement_attempts.max(1) as usize;
+        let mut placed = 0;
+
+        for _ in 0..max_candidates {
+            if placed == wanted || self.progress.is_cancelled() {
+                break;
+            }
+
+            let star = candidate(rng);
+            let too_close = separation > 0.0
+                && self.grid.near(star.position).any(|i| {
+                    vec3_len(vec3_sub(self.stars[i].position, star.position)) < separation
+                });
+
+            if !too_close {
+                self.grid.insert(self.stars.len(), star.position);
+                self.stars.push(star);
+                placed += 1;
+                self.progress.star_placed();
+            }
+        }
+        placed
+    }
+}
+
+fn generate_clusters(
+    config: &GalaxyConfig,
+    sampler: &PositionSampler,
+    rng: &mut impl Rng,
+) -> Vec<ClusterPlacement> {
+    let radius = config.galaxy_radius;
+    let mut clusters = Vec::new();
+
+    for _ in 0..config.open_cluster_count {
+        // Open clusters are born in the arms, wherever the shape puts them.
+        let center = sampler.sample_shape(rng);
+        clusters.push(ClusterPlacement {
+            cluster_type: ClusterType::Open,
+            center: [center[0], center[1], sampler.disk_height(rng)],
+            radius: config.open_cluster_radius * radius,
+        });
+    }
+
+    for _ in 0..config.globular_cluster_count {
+        // The halo is round, so globulars sit well above and below the disk.
+        let r = rng.random_range(0.5..config.halo_radius.max(0.5 + f32::EPSILON)) * radius;
+        clusters.push(ClusterPlacement {
+            cluster_type: ClusterType::Globular,
+            center: uniform_direction(rng, r),
+            radius: config.globular_cluster_radius * radius,
+        });
+    }
+    clusters
+}
+
+pub fn generate_system_positions(
+    config: &GalaxyConfig,
+    rng: &mut impl Rng,
+    progress: &GenerationProgress,
+) -> GalaxyLayout {
+    let sampler = PositionSampler::new(config, rng);
+    let clusters = generate_clusters(config, &sampler, rng);
+    let mut placer = Placer::new(config, progress);
+
+    for (index, cluster) in clusters.iter().enumerate() {
+        let (wanted, population) = match cluster.cluster_type {
+            ClusterType::Open => (config.open_cluster_stars, StellarPopulation::OpenCluster),
+            ClusterType::Globular => (config.globular_cluster_stars, StellarPopulation::Globular),
+        };
+        let wanted = (wanted as usize).min(config.num_stars - placer.stars.len());
+
+        placer.place(rng, wanted, |rng| {
+            let offset = plummer_offset(rng, cluster.radius);
+            StarPlacement {
+                position: [
+                    cluster.center[0] + offset[0],
+                    cluster.center[1] + offset[1],
+                    cluster.center[2] + offset[2],
+                ],
+                population,
+                cluster: Some(index),
+            }
+        });
+    }
+
+    let field = config.num_stars - placer.stars.len();
+    placer.place(rng, field, |rng| sampler.sample(rng));
+
+    if placer.stars.len() < config.num_stars && !progress.is_cancelled() {
+        warn!(
+            "Placed {} of {} stars, the galaxy is too dense for a separation of {}",
+            placer.stars.len(),
+            config.num_stars,
+            config.min_star_separation
+        );
+    }
+
+    GalaxyLayout {
+        stars: placer.stars,
+        clusters,
+    }
+}
+
+/// Naming culture of the wedge the position falls into.
+pub fn culture_at(naming: &NamingConfig, position: Vector2<f32>) -> Option<&NameCulture> {
+    if naming.cultures.is_empty() {
+        return None;
+    }
+    let angle = position[1].atan2(position[0]).rem_euclid(f32::consts::TAU);
+    let index = (angle / f32::consts::TAU * naming.cultures.len() as f32) as usize;
+    naming.cultures.get(index.min(naming.cultures.len() - 1))
+}
+
+/// Everything `generate_galaxy` produces, ready to be spawned.
+pub struct GeneratedGalaxy {
+    pub stars: Vec<GeneratedStar>,
+    pub clusters: Vec<StarClusterBundle>,
+    pub black_hole: Option<SupermassiveBlackHoleBundle>,
+    pub nebulae: Vec<NebulaBundle>,
+    pub sectors: GalaxySectors,
+    pub lanes: HyperlaneGraph,
+}
+
+pub struct GeneratedStar {
+    pub star: StarBundle,
+    pub position: Vector3<f32>,
+    pub seed: SystemSeed,
+    pub sector: SectorId,
+    pub cluster: Option<ClusterMembership>,
+    pub companions: Vec<CompanionBundle>,
+    /// Bodies known in advance. `None` leaves them to be generated from
+    /// `seed` when the system is first needed.
+    pub bodies: Option<Vec<CelestialBodyBundle>>,
+}
+
+impl GeneratedStar {
+    /// What the star's planets are made from later, if they are not known.
+    pub fn pending(&self) -> Option<PendingGeneration> {
+        self.bodies.is_none().then(|| PendingGeneration {
+            star: (&self.star).into(),
+            companions: self
+                .companions
+                .iter()
+                .map(SystemCompanion::from)
+                .sorted_by(|a, b| a.separation.total_cmp(&b.separation))
+                .collect(),
+            exploded: Vec::new(),
+        })
+    }
+}
+
+/// Builds the whole galaxy from the configs without touching the world, so it
+/// can run on a task pool. Returns `None` once `progress` is cancelled.
+pub fn generate_galaxy(
+    config: &GalaxyConfig,
+    naming: &NamingConfig,
+    progress: &GenerationProgress,
+) -> Option<GeneratedGalaxy> {
+    if let Some(catalogue) = config.catalogue.as_ref() {
+        return generate_catalogue_galaxy(config, catalogue, naming, progress);
+    }
+
+    let mut rng = config.rng();
+    let layout = generate_system_positions(config, &mut rng, progress);
+    if progress.is_cancelled() {
+        return None;
+    }
+
+    let mut names = NameGenerator::default();
+    let sectors = generate_sectors(config, naming, &mut rng, &mut names, &layout.stars);
+
+    let clusters = layout
+        .clusters
+        .iter()
+        .enumerate()
+        .map(|(index, cluster)| {
+            let id = ID::from_rng(&mut rng);
+            let name = match culture_at(naming, planar(cluster.center)) {
+                Some(culture) => names.proper_name(&mut rng, culture),
+                None => format!("CL {}", index + 1),
+            };
+            let name = match cluster.cluster_type {
+                ClusterType::Open => format!("{} Cluster", name),
+                ClusterType::Globular => format!("{} Globular", name),
+            };
+
+            StarClusterBundle {
+                id,
+                name: Name(name),
+                cluster_type: CClusterType(cluster.cluster_type),
+                radius: ClusterRadius(cluster.radius),
+                spatial: SpatialBundle::from_position(cluster.center),
+                cluster: StarCluster,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut stars = Vec::with_capacity(layout.stars.len());
+    for placement in layout.stars.iter() {
+        if progress.is_cancelled() {
+            return None;
+        }
+
+        let position = placement.position;
+        let star = Star::generate(&mut rng, placement.population);
+        let metallicity = metallicity_at(config, &mut rng, placement);
+        let star = star.with_metallicity(metallicity);
+        let mut companions = Star::generate_companions(&mut rng, placement.population, &star);
+        let sector = sectors.sector_at(planar(position)).unwrap_or_default();
+        let designation = names.designation(&sectors.sectors[sector].code, star.star_type.0);
+        let name = match culture_at(naming, planar(position)) {
+            Some(culture) if rng.random::<f32>() < naming.proper_name_fraction => {
+                names.proper_name(&mut rng, culture)
+            }
+            _ => designation.clone(),
+        };
+        let seed = SystemSeed(rng.random());
+        for (companion, letter) in companions.iter_mut().zip(['B', 'C']) {
+            companion.name = Name(format!("{} {}", name, letter));
+        }
+
+        stars.push(GeneratedStar {
+            star: star.with_name(&name).with_designation(&designation),
+            position,
+            seed,
+            sector: SectorId(sector),
+            cluster: placement
+                .cluster
+                .map(|cluster| ClusterMembership(clusters[cluster].id.clone())),
+            companions,
+            bodies: None,
+        });
+        progress.system_generated();
+    }
+
+    let black_hole = config.central_black_hole.then(|| {
+        let name = match culture_at(naming, [0.0, 0.0]) {
+            Some(culture) => format!("{} A*", names.proper_name(&mut rng, culture)),
+            None => "Galactic Core".to_string(),
+        };
+        SupermassiveBlackHoleBundle {
+            id: ID::from_rng(&mut rng),
+            name: Name(name),
+            mass: Mass(config.black_hole_mass),
+            spatial: SpatialBundle::from_position([0.0, 0.0, 0.0]),
+            black_hole: SupermassiveBlackHole,
+        }
+    });
+
+    let nebulae = generate_nebulae(config, naming, &mut rng, &mut names, &layout.stars);
+
+    let lanes = build_hyperlanes(
+        config,
+        stars
+            .iter()
+            .map(|s| (s.star.id.clone(), s.position))
+            .collect(),
+        progress,
+    )?;
+
+    Some(GeneratedGalaxy {
+        stars,
+        clusters,
+        black_hole,
+        nebulae,
+        sectors,
+        lanes,
+    })
+}
+
+/// Fired once a generated galaxy has been spawned into the world.
+#[derive(Event)]
+pub struct GalaxyGenerated;
+
+/// Picks up the finished generation task and spawns its result.
+pub fn spawn_generated_galaxy(
+    mut commands: Commands,
+    mut generation: ResMut<GalaxyGeneration>,
+    mut screen: ResMut<CurrentScreen>,
+    mut generated: EventWriter<GalaxyGenerated>,
+) {
+    let Some(task) = generation.task.as_mut() else {
+        return;
+    };
+    let Some(result) = block_on(poll_once(task)) else {
+        return;
+    };
+    generation.task = None;
+
+    // Cancelled or failed, either way there is no game to show.
+    let Some(galaxy) = result else {
+        screen.0 = Screen::MainMenu;
+        return;
+    };
+
+    for cluster in galaxy.clusters {
+        commands.spawn(cluster);
+    }
+
+    for star in galaxy.stars {
+        let pending = star.pending();
+        let mut entity = commands.spawn((
+            star.star,
+            SpatialBundle::from_position(star.position),
+            star.seed,
+            star.sector,
+        ));
+        if let Some(cluster) = star.cluster {
+            entity.insert(cluster);
+        }
+        if let Some(pending) = pending {
+            entity.insert(pending);
+        }
+        entity.with_children(|parent| {
+            for companion in star.companions {
+                parent.spawn(companion);
+            }
+            for body in star.bodies.into_iter().flatten() {
+                parent.spawn(body);
+            }
+        });
     }
-    positions
+
+    if let Some(black_hole) = galaxy.black_hole {
+        commands.spawn(black_hole);
+    }
+
+    for nebula in galaxy.nebulae {
+        commands.spawn(nebula);
+    }
+
+    commands.insert_resource(galaxy.sectors);
+    commands.insert_resource(galaxy.lanes);
+    commands.insert_resource(GameTime::default());
+
+    screen.0 = Screen::Game;
+    generated.send(GalaxyGenerated);
 }
 
-pub fn generate_star_systems(mut commands: Commands, config: Res<GalaxyConfig>) {
-    let positions = generate_system_positions(config);
+/// Polar sector grid reaching out to the furthest star.
+pub fn generate_sectors(
+    config: &GalaxyConfig,
+    naming: &NamingConfig,
+    rng: &mut impl Rng,
+    names: &mut NameGenerator,
+    placements: &[StarPlacement],
+) -> GalaxySectors {
+    let extent = placements
+        .iter()
+        .map(|p| vec2_len(planar(p.position)))
+        .fold(config.galaxy_radius, f32::max);
+    let rings = config.sector_rings.max(1);
+
+    let mut grid = GalaxySectors {
+        ring_width: extent / rings as f32,
+        inner_wedges: config.sector_inner_wedges,
+        sectors: Vec::new(),
+    };
+
+    for ring in 0..rings {
+        let wedges = grid.wedges_in_ring(ring);
+        for wedge in 0..wedges {
+            let mut sector = Sector {
+                name: String::new(),
+                code: String::new(),
+                ring,
+                inner_radius: ring as f32 * grid.ring_width,
+                outer_radius: (ring + 1) as f32 * grid.ring_width,
+                start_angle: f32::consts::TAU * wedge as f32 / wedges as f32,
+                end_angle: f32::consts::TAU * (wedge + 1) as f32 / wedges as f32,
+            };
+
+            let index = grid.sectors.len();
+            let root = match culture_at(naming, sector.label_position()) {
+                Some(culture) => names.proper_name(rng, culture),
+                None => format!("Sector {}", index + 1),
+            };
+            sector.name = match ring {
+                0 => format!("{} Core", root),
+                r if r + 1 == rings => format!("{} Rim", root),
+                1 => format!("{} Reach", root),
+                _ => format!("{} March", root),
+            };
+            sector.code = format!(
+                "{}{}",
+                root.chars()
+                    .filter(|c| c.is_alphabetic())
+                    .take(2)
+                    .collect::<String>()
+                    .to_uppercase(),
+                index
+            );
 
-    for position in positions {
-        commands.spawn((Star::generate(), SpatialBundle::from_position(position)));
+            grid.sectors.push(sector);
+        }
     }
+    grid
+}
+
+/// Nebulae anchored on disk stars, so they follow the arms of whatever shape
+/// the galaxy has. Each cloud is a set of puffs kept where fractal noise is dense.
+fn generate_nebulae(
+    config: &GalaxyConfig,
+    naming: &NamingConfig,
+    rng: &mut impl Rng,
+    names: &mut NameGenerator,
+    placements: &[StarPlacement],
+) -> Vec<NebulaBundle> {
+    let anchors = placements
+        .iter()
+        .filter(|p| p.population == StellarPopulation::Disk)
+        .map(|p| p.position)
+        .collect::<Vec<_>>();
+    if anchors.is_empty() {
+        return Vec::new();
+    }
+
+    let fbm = Fbm::<Perlin>::new(config.seed.wrapping_add(1));
+
+    (0..config.nebula_count)
+        .map(|i| {
+            let center = anchors[rng.random_range(0..anchors.len())];
+            let radius = config.nebula_radius * config.galaxy_radius * rng.random_range(0.5..1.5);
+            let nebula_type = match rng.random_range(0..10) {
+                0..=4 => NebulaType::Emission,
+                5..=7 => NebulaType::Dark,
+                _ => NebulaType::Reflection,
+            };
+
+            let scale = 3.0 / radius as f64;
+            let mut puffs = Vec::new();
+            for _ in 0..48 {
+                // Flattened like the disk the cloud lies in.
+                let point = [
+                    center[0] + gaussian(rng) * 0.5 * radius,
+                    center[1] + gaussian(rng) * 0.5 * radius,
+                    center[2] + gaussian(rng) * 0.15 * radius,
+                ];
+                let density = fbm.get([point[0] as f64 * scale, point[1] as f64 * scale]) as f32;
+                if density > -0.1 {
+                    puffs.push((point, radius * (0.25 + 0.25 * density).max(0.1)));
+                }
+            }
+            if puffs.is_empty() {
+                puffs.push((center, 0.3 * radius));
+            }
+
+            let name = match culture_at(naming, planar(center)) {
+                Some(culture) => format!("{} Nebula", names.proper_name(rng, culture)),
+                None => format!("Nebula {}", i + 1),
+            };
+
+            NebulaBundle {
+                id: ID::from_rng(rng),
+                name: Name(name),
+                nebula_type: CNebulaType(nebula_type),
+                shape: NebulaShape { puffs },
+                nebula: Nebula,
+            }
+        })
+        .collect()
 }
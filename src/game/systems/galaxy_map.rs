@@ -1,23 +1,246 @@
-use bevy::prelude::{Query, ResMut};
+use std::collections::HashMap;
 
-use crate::game::components::{
-    common::ID,
-    galaxy_map::{GalaxyMap, StarData},
-    spatial::Position,
-    star::{CStarClass, CStarType, Star},
+use bevy::{
+    ecs::query::QueryItem,
+    prelude::{EventReader, Has, Query, Res, ResMut, With},
 };
 
+use crate::game::{
+    components::{
+        cluster::{CClusterType, ClusterMembership, ClusterRadius, StarCluster},
+        common::{Name, Temperature, ID},
+        galaxy_map::{ClusterData, CompanionData, CoreData, GalaxyMap, NebulaData, StarData},
+        nebula::{CNebulaType, NebulaShape},
+        orbit::{OrbitalElements, AU},
+        planet::SystemId,
+        spatial::Position,
+        star::{
+            ActiveFlare, Age, CEvolutionStage, CStarClass, CStarType, CatalogueDesignation,
+            Companion, FlareActivity, Luminosity, LuminosityModifier, Metallicity, SectorId,
+            SpectralSubclass, Star, SupermassiveBlackHole, Variability,
+        },
+    },
+    resourses::sectors::GalaxySectors,
+    systems::stellar_evolution::StarEvolved,
+};
+
+/// What the map shows of a star.
+type MapStar = (
+    &'static Star,
+    &'static Position,
+    &'static CStarType,
+    &'static SpectralSubclass,
+    &'static CStarClass,
+    &'static ID,
+    &'static Name,
+    &'static CatalogueDesignation,
+    &'static Metallicity,
+    (&'static Temperature, &'static Luminosity),
+    (&'static Age, &'static CEvolutionStage),
+    (&'static Variability, &'static FlareActivity),
+    Option<&'static ClusterMembership>,
+    &'static SectorId,
+);
+
+/// What the map shows of a companion.
+type MapCompanion = (
+    &'static SystemId,
+    &'static Name,
+    &'static CStarType,
+    &'static SpectralSubclass,
+    &'static CStarClass,
+    (&'static Temperature, &'static Luminosity),
+    &'static OrbitalElements,
+);
+
+/// What evolution changes about a star on the map.
+type EvolvedStar = (
+    &'static ID,
+    &'static CStarType,
+    &'static SpectralSubclass,
+    &'static CStarClass,
+    (&'static Temperature, &'static Luminosity),
+    (&'static Age, &'static CEvolutionStage),
+    (&'static Variability, &'static FlareActivity),
+);
+
+/// Companions on the map, by the primary of their system.
+fn companion_data<'a>(
+    companions: impl Iterator<Item = QueryItem<'a, MapCompanion>>,
+) -> HashMap<ID, Vec<CompanionData>> {
+    let mut data = HashMap::<ID, Vec<CompanionData>>::new();
+    for (system, name, star_type, subclass, star_class, (temperature, luminosity), orbit) in
+        companions
+    {
+        data.entry(system.0.clone())
+            .or_default()
+            .push(CompanionData {
+                name: name.0.clone(),
+                star_type: star_type.0,
+                subclass: subclass.0,
+                star_class: star_class.0,
+                temperature: temperature.0,
+                luminosity: luminosity.0,
+                separation: (orbit.semi_major_axis / AU) as f32,
+            });
+    }
+    data
+}
+
 pub fn update_galaxy_map(
     mut map: ResMut<GalaxyMap>,
-    query: Query<(&Star, &Position, &CStarType, &CStarClass, &ID)>,
+    query: Query<MapStar>,
+    core_query: Query<(&Name, &Position), With<SupermassiveBlackHole>>,
+    nebula_query: Query<(&Name, &CNebulaType, &NebulaShape)>,
+    cluster_query: Query<(&ID, &Name, &CClusterType, &Position, &ClusterRadius), With<StarCluster>>,
+    companion_query: Query<MapCompanion, With<Companion>>,
+    sectors: Res<GalaxySectors>,
 ) {
+    let mut companions = companion_data(companion_query.iter());
+
+    let cluster_names = cluster_query
+        .iter()
+        .map(|(id, name, _, _, _)| (id.clone(), name.0.clone()))
+        .collect::<HashMap<_, _>>();
+
     let mut stars = map.stars.lock().unwrap();
-    for (_, position, star_type, star_class, id) in query.iter() {
+    stars.clear();
+    for (
+        _,
+        position,
+        star_type,
+        subclass,
+        star_class,
+        id,
+        name,
+        designation,
+        metallicity,
+        (temperature, luminosity),
+        (age, stage),
+        (variability, flares),
+        cluster,
+        sector,
+    ) in query.iter()
+    {
         stars.push(StarData {
             id: id.clone(),
+            name: name.0.clone(),
+            designation: designation.0.clone(),
             position: position.0,
             star_type: star_type.0,
+            subclass: subclass.0,
             star_class: star_class.0,
+            metallicity: metallicity.0,
+            temperature: temperature.0,
+            luminosity: luminosity.0,
+            age: age.0,
+            stage: stage.0,
+            variability: *variability,
+            flares: *flares,
+            output: 1.0,
+            flaring: false,
+            cluster: cluster.and_then(|c| cluster_names.get(&c.0).cloned()),
+            sector: sectors
+                .sectors
+                .get(sector.0)
+                .map(|s| s.name.clone())
+                .unwrap_or_default(),
+            companions: companions.remove(id).unwrap_or_default(),
         });
     }
+    drop(stars);
+    map.hovered = None;
+
+    map.core = core_query.iter().next().map(|(name, position)| CoreData {
+        name: name.0.clone(),
+        position: position.0,
+    });
+
+    map.nebulae = nebula_query
+        .iter()
+        .map(|(name, nebula_type, shape)| NebulaData {
+            name: name.0.clone(),
+            nebula_type: nebula_type.0,
+            shape: shape.clone(),
+        })
+        .collect();
+
+    map.clusters = cluster_query
+        .iter()
+        .map(|(_, name, cluster_type, position, radius)| ClusterData {
+            name: name.0.clone(),
+            cluster_type: cluster_type.0,
+            center: position.0,
+            radius: radius.0,
+        })
+        .collect();
+
+    map.sectors = sectors.sectors.clone();
+}
+
+/// Refreshes the systems on the map whose stars evolved, leaving the rest
+/// of the map as it is.
+pub fn update_evolved_stars(
+    map: Res<GalaxyMap>,
+    mut evolved: EventReader<StarEvolved>,
+    query: Query<EvolvedStar, With<Star>>,
+    companion_query: Query<MapCompanion, With<Companion>>,
+) {
+    let systems = evolved
+        .read()
+        .map(|event| event.system.clone())
+        .collect::<Vec<_>>();
+    if systems.is_empty() {
+        return;
+    }
+    let mut companions = companion_data(
+        companion_query
+            .iter()
+            .filter(|(system, ..)| systems.contains(&system.0)),
+    );
+
+    for star in map.stars.lock().unwrap().iter_mut() {
+        if !systems.contains(&star.id) {
+            continue;
+        }
+        let Some((
+            _,
+            star_type,
+            subclass,
+            star_class,
+            (temperature, luminosity),
+            (age, stage),
+            (variability, flares),
+        )) = query.iter().find(|(id, ..)| **id == star.id)
+        else {
+            continue;
+        };
+        star.star_type = star_type.0;
+        star.subclass = subclass.0;
+        star.star_class = star_class.0;
+        star.temperature = temperature.0;
+        star.luminosity = luminosity.0;
+        star.age = age.0;
+        star.stage = stage.0;
+        star.variability = *variability;
+        star.flares = *flares;
+        star.companions = companions.remove(&star.id).unwrap_or_default();
+    }
+}
+
+/// Refreshes the fast-changing output of every star on the map.
+pub fn update_star_output(
+    map: Res<GalaxyMap>,
+    query: Query<(&ID, &LuminosityModifier, Has<ActiveFlare>), With<Star>>,
+) {
+    let outputs = query
+        .iter()
+        .map(|(id, modifier, flaring)| (id, (modifier.0, flaring)))
+        .collect::<HashMap<_, _>>();
+    for star in map.stars.lock().unwrap().iter_mut() {
+        if let Some(&(output, flaring)) = outputs.get(&star.id) {
+            star.output = output;
+            star.flaring = flaring;
+        }
+    }
 }
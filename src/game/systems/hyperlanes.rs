@@ -0,0 +1,433 @@
+use std::collections::HashSet;
+
+use vecmath::{vec3_add, vec3_dot, vec3_len, vec3_scale, vec3_sub, Vector3};
+
+use crate::game::{
+    components::common::ID,
+    resourses::{
+        galaxy::{GalaxyConfig, LaneRule},
+        generation::GenerationProgress,
+        hyperlanes::HyperlaneGraph,
+    },
+};
+
+use super::spatial_grid::SpatialGrid;
+
+/// Elevation bands in degrees and how many azimuth sectors each is cut
+/// into. No two directions in the same cone are 60° or more apart.
+const CONES: [(f32, f32, usize); 7] = [
+    (-90.0, -75.0, 1),
+    (-75.0, -45.0, 6),
+    (-45.0, -15.0, 8),
+    (-15.0, 15.0, 8),
+    (15.0, 45.0, 8),
+    (45.0, 75.0, 6),
+    (75.0, 90.0, 1),
+];
+const CONE_COUNT: usize = 38;
+
+pub fn build_hyperlanes(
+    config: &GalaxyConfig,
+    nodes: Vec<(ID, Vector3<f32>)>,
+    progress: &GenerationProgress,
+) -> Option<HyperlaneGraph> {
+    let positions = nodes.iter().map(|(_, p)| *p).collect::<Vec<_>>();
+    let lanes = find_lanes(
+        &positions,
+        config.max_lane_length,
+        config.lane_rule,
+        progress,
+    )?;
+
+    Some(HyperlaneGraph::new(nodes, lanes))
+}
+
+/// Pairs of star indices connected by a lane, `None` once `progress` is
+/// cancelled.
+pub fn find_lanes(
+    positions: &[Vector3<f32>],
+    max_length: f32,
+    rule: LaneRule,
+    progress: &GenerationProgress,
+) -> Option<Vec<(usize, usize)>> {
+    let Some(extent) = Extent::of(positions) else {
+        return Some(Vec::new());
+    };
+    // About one star per cell, however long lanes may be.
+    let spacing = (extent.width * extent.depth / positions.len() as f32).sqrt();
+    let grid = SpatialGrid::from_positions(positions, spacing.min(max_length));
+    let distance = |a: usize, b: usize| vec3_len(vec3_sub(positions[a], positions[b]));
+
+    if rule == LaneRule::Proximity {
+        let mut lanes = Vec::new();
+        for (a, &position) in positions.iter().enumerate() {
+            if progress.is_cancelled() {
+                return None;
+            }
+            lanes.extend(
+                grid.within(position, max_length)
+                    .filter(|&b| b > a && distance(a, b) <= max_length)
+                    .map(|b| (a, b)),
+            );
+            progress.star_linked();
+        }
+        return Some(lanes);
+    }
+
+    let search = ConeSearch {
+        positions,
+        occupancy: Occupancy::of(positions, 8.0 * grid.cell_size()),
+        grid,
+        extent,
+        max_length,
+        centres: cone_centres(),
+    };
+    let mut candidates = Vec::with_capacity(positions.len());
+    for a in 0..positions.len() {
+        if progress.is_cancelled() {
+            return None;
+        }
+        candidates.push(search.nearest_per_cone(a));
+        progress.star_linked();
+    }
+    let grid = search.grid;
+
+    let mut lanes = Vec::new();
+    for (a, near) in candidates.iter().enumerate() {
+        // Relative neighbours are the nearest in their cone from both ends.
+        for &b in near
+            .iter()
+            .filter(|&&b| b > a && candidates[b].contains(&a))
+        {
+            let length = distance(a, b);
+            // Any witness closer to both ends lies within `length * √3 / 2`
+            // of the middle of the lane, searched from there outwards.
+            let middle = vec3_scale(vec3_add(positions[a], positions[b]), 0.5);
+            let rings = (0.87 * length / grid.cell_size()).ceil() as i32;
+            let blocked = (0..=rings).any(|ring| {
+                grid.ring(middle, ring)
+                    .any(|c| c != a && c != b && distance(a, c).max(distance(b, c)) < length)
+            });
+
+            if !blocked {
+                lanes.push((a, b));
+            }
+        }
+    }
+    Some(lanes)
+}
+
+/// Bounding box of the stars.
+struct Extent {
+    width: f32,
+    depth: f32,
+    bottom: f32,
+    top: f32,
+}
+
+impl Extent {
+    fn of(positions: &[Vector3<f32>]) -> Option<Self> {
+        let first = *positions.first()?;
+        let (min, max) = positions.iter().fold((first, first), |(min, max), p| {
+            (
+                [min[0].min(p[0]), min[1].min(p[1]), min[2].min(p[2])],
+                [max[0].max(p[0]), max[1].max(p[1]), max[2].max(p[2])],
+            )
+        });
+        Some(Self {
+            width: (max[0] - min[0]).max(f32::EPSILON),
+            depth: (max[1] - min[1]).max(f32::EPSILON),
+            bottom: min[2],
+            top: max[2],
+        })
+    }
+}
+
+/// Coarse cells holding at least one star, by their lower corners.
+struct Occupancy {
+    cell_size: f32,
+    cells: Vec<[f32; 2]>,
+}
+
+impl Occupancy {
+    fn of(positions: &[Vector3<f32>], cell_size: f32) -> Self {
+        let cells = positions
+            .iter()
+            .map(|p| {
+                (
+                    (p[0] / cell_size).floor() as i32,
+                    (p[1] / cell_size).floor() as i32,
+                )
+            })
+            .collect::<HashSet<_>>();
+        Self {
+            cell_size,
+            cells: cells
+                .into_iter()
+                .map(|(x, y)| [x as f32 * cell_size, y as f32 * cell_size])
+                .collect(),
+        }
+    }
+
+    /// Whether a star further than `reach` and no further than `max_length`
+    /// from `from` could lie between azimuths `start` and `end`, in degrees.
+    fn any_between(
+        &self,
+        from: Vector3<f32>,
+        start: f32,
+        end: f32,
+        reach: f32,
+        max_length: f32,
+    ) -> bool {
+        let middle = (start + end) / 2.0;
+        let half = (end - start) / 2.0;
+        self.cells.iter().any(|&[x, y]| {
+            let corners = [
+                [x, y],
+                [x + self.cell_size, y],
+                [x, y + self.cell_size],
+                [x + self.cell_size, y + self.cell_size],
+            ]
+            .map(|[cx, cy]| [cx - from[0], cy - from[1]]);
+            let farthest = corners
+                .iter()
+                .map(|[dx, dy]| dx.hypot(*dy))
+                .fold(0.0, f32::max);
+            let nearest_x = (x - from[0]).max(from[0] - x - self.cell_size).max(0.0);
+            let nearest_y = (y - from[1]).max(from[1] - y - self.cell_size).max(0.0);
+            if farthest < reach || nearest_x.hypot(nearest_y) > max_length {
+                return false;
+            }
+            if nearest_x == 0.0 && nearest_y == 0.0 {
+                return true;
+            }
+            // Angles off the middle of the sector, measured around the cell's
+            // own centre so the span never wraps.
+            let wrap = |angle: f32| (angle + 180.0).rem_euclid(360.0) - 180.0;
+            let centre = wrap(
+                (y + self.cell_size / 2.0 - from[1])
+                    .atan2(x + self.cell_size / 2.0 - from[0])
+                    .to_degrees()
+                    - middle,
+            );
+            let (low, high) = corners.iter().fold((f32::MAX, f32::MIN), |(low, high), c| {
+                let angle = centre + wrap(c[1].atan2(c[0]).to_degrees() - middle - centre);
+                (low.min(angle), high.max(angle))
+            });
+            high >= -half && low <= half
+        })
+    }
+}
+
+/// Looks for the stars that can be relative neighbours.
+struct ConeSearch<'a> {
+    positions: &'a [Vector3<f32>],
+    grid: SpatialGrid,
+    occupancy: Occupancy,
+    extent: Extent,
+    max_length: f32,
+    centres: [(Vector3<f32>, f32); CONE_COUNT],
+}
+
+impl ConeSearch<'_> {
+    /// Nearest star to `a` within `max_length` in each cone of directions,
+    /// the only stars that can be its relative neighbours: a nearer star
+    /// less than 60° off the same direction is closer to both ends.
+    /// Searches outwards ring by ring until every cone has such a nearer
+    /// star for whatever lies beyond, is too steep for the stars that are
+    /// left to reach, or has none of them in its sector.
+    fn nearest_per_cone(&self, a: usize) -> Vec<usize> {
+        let position = self.positions[a];
+        let mut nearest: [Option<(f32, usize)>; CONE_COUNT] = [None; CONE_COUNT];
+        // Distance of the nearest star less than 60° off every direction
+        // in the cone.
+        let mut covered = [f32::INFINITY; CONE_COUNT];
+        let mut empty = [false; CONE_COUNT];
+        let cell_size = self.grid.cell_size();
+        let last_ring = (self.extent.width.max(self.extent.depth) / cell_size).ceil() as i32 + 1;
+
+        for ring in 0..=last_ring {
+            for b in self.grid.ring(position, ring).filter(|&b| b != a) {
+                let offset = vec3_sub(self.positions[b], position);
+                let length = vec3_len(offset);
+                if length > self.max_length {
+                    continue;
+                }
+                let cone = cone(offset, length);
+                if nearest[cone].is_none_or(|(d, _)| length < d) {
+                    nearest[cone] = Some((length, b));
+                }
+                let direction = vec3_scale(offset, 1.0 / length);
+                for (covered, (centre, cover)) in covered.iter_mut().zip(self.centres.iter()) {
+                    if vec3_dot(direction, *centre) > *cover {
+                        *covered = covered.min(length);
+                    }
+                }
+            }
+
+            let reach = ring as f32 * cell_size;
+            if reach > self.max_length {
+                break;
+            }
+            // Looking for empty sectors is slow, so only now and then.
+            let look_for_empty = ring >= 16 && (ring as u32).is_power_of_two();
+            let mut first = 0;
+            let mut done = true;
+            for &(low, high, sectors) in CONES.iter() {
+                // The stars left are at least `reach` away horizontally, and
+                // only so far above or below.
+                let room = if low >= 0.0 {
+                    self.extent.top - position[2]
+                } else if high <= 0.0 {
+                    position[2] - self.extent.bottom
+                } else {
+                    f32::INFINITY
+                };
+                let too_steep = room.atan2(reach).to_degrees() < low.abs().min(high.abs());
+                for sector in 0..sectors {
+                    let cone = first + sector;
+                    if too_steep || empty[cone] || covered[cone] <= reach {
+                        continue;
+                    }
+                    if look_for_empty && sectors > 1 {
+                        let width = 360.0 / sectors as f32;
+                        empty[cone] = !self.occupancy.any_between(
+                            position,
+                            sector as f32 * width,
+                            (sector + 1) as f32 * width,
+                            reach,
+                            self.max_length,
+                        );
+                    }
+                    done &= empty[cone];
+                }
+                first += sectors;
+            }
+            if done {
+                break;
+            }
+        }
+        nearest.into_iter().flatten().map(|(_, b)| b).collect()
+    }
+}
+
+/// Unit vector through the middle of each cone, and the cosine of the
+/// angle within which a star is less than 60° off every direction in it.
+fn cone_centres() -> [(Vector3<f32>, f32); CONE_COUNT] {
+    let direction = |azimuth: f32, elevation: f32| {
+        let (sin_a, cos_a) = azimuth.to_radians().sin_cos();
+        let (sin_e, cos_e) = elevation.to_radians().sin_cos();
+        [cos_e * cos_a, cos_e * sin_a, sin_e]
+    };
+    let mut centres = [([0.0; 3], 1.0); CONE_COUNT];
+    let mut first = 0;
+    for &(low, high, sectors) in CONES.iter() {
+        let width = 360.0 / sectors as f32;
+        for sector in 0..sectors {
+            let start = sector as f32 * width;
+            let centre = direction(start + width / 2.0, (low + high) / 2.0);
+            // Widest angle from the middle to the edge, sampled, plus a
+            // degree to spare.
+            let radius = (0..=8)
+                .flat_map(|i| (0..=8).map(move |j| (i as f32 / 8.0, j as f32 / 8.0)))
+                .map(|(i, j)| {
+                    let edge = direction(start + i * width, low + j * (high - low));
+                    vec3_dot(edge, centre).clamp(-1.0, 1.0).acos().to_degrees()
+                })
+                .fold(0.0, f32::max)
+                + 1.0;
+            centres[first + sector] = (centre, (60.0 - radius).to_radians().cos());
+        }
+        first += sectors;
+    }
+    centres
+}
+
+/// Index into `CONES` of the direction of `offset`, `length` long.
+fn cone(offset: Vector3<f32>, length: f32) -> usize {
+    let elevation = (offset[2] / length).clamp(-1.0, 1.0).asin().to_degrees();
+    let azimuth = offset[1].atan2(offset[0]).to_degrees().rem_euclid(360.0);
+    let mut first = 0;
+    for &(_, high, sectors) in CONES.iter() {
+        if elevation <= high {
+            let sector = (azimuth / 360.0 * sectors as f32) as usize;
+            return first + sector.min(sectors - 1);
+        }
+        first += sectors;
+    }
+    CONE_COUNT - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn cones_are_narrow() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut directions = vec![Vec::new(); CONE_COUNT];
+        for _ in 0..20_000 {
+            let direction: Vector3<f32> = [
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+                rng.random_range(-1.0..1.0),
+            ];
+            let length = vec3_len(direction);
+            if length > 0.01 && length <= 1.0 {
+                let unit = direction.map(|c| c / length);
+                directions[cone(direction, length)].push(unit);
+            }
+        }
+        for cone in directions {
+            for u in cone.iter() {
+                for v in cone.iter() {
+                    assert!(vecmath::vec3_dot(*u, *v) > 0.5);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn relative_neighbourhood_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(2);
+        // A thin disk, a thick slab, and two clumps far apart.
+        for (height, clumps) in [(5.0, 1), (100.0, 1), (5.0, 2)] {
+            let positions = (0..400)
+                .map(|i| {
+                    let offset = if i % clumps == 0 { 0.0 } else { 600.0 };
+                    [
+                        offset + rng.random_range(-100.0..100.0),
+                        rng.random_range(-100.0..100.0),
+                        rng.random_range(-height..height),
+                    ]
+                })
+                .collect::<Vec<Vector3<f32>>>();
+            let distance = |a: usize, b: usize| vec3_len(vec3_sub(positions[a], positions[b]));
+
+            let mut expected = Vec::new();
+            for a in 0..positions.len() {
+                for b in a + 1..positions.len() {
+                    let length = distance(a, b);
+                    if (0..positions.len())
+                        .all(|c| c == a || c == b || distance(a, c).max(distance(b, c)) >= length)
+                    {
+                        expected.push((a, b));
+                    }
+                }
+            }
+
+            let progress = GenerationProgress::default();
+            let mut lanes = find_lanes(
+                &positions,
+                1000.0,
+                LaneRule::RelativeNeighbourhood,
+                &progress,
+            )
+            .unwrap();
+            lanes.sort();
+            assert_eq!(lanes, expected);
+        }
+    }
+}
@@ -1,5 +1,11 @@
+pub mod catalogue;
 pub mod galaxy_generation;
 pub mod galaxy_map;
+pub mod hyperlanes;
+pub mod naming;
+pub mod spatial_grid;
 pub mod star_system_generation;
+pub mod stellar_evolution;
 pub mod system_map;
 pub mod ui;
+pub mod variability;
@@ -0,0 +1,67 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{seq::IndexedRandom, Rng};
+
+use crate::game::{components::star::StarType, resourses::naming::NameCulture};
+
+/// Hands out star names and catalogue designations that are unique within
+/// one galaxy.
+#[derive(Default)]
+pub struct NameGenerator {
+    used: HashSet<String>,
+    catalogue_counters: HashMap<String, u32>,
+}
+
+impl NameGenerator {
+    pub fn proper_name(&mut self, rng: &mut impl Rng, culture: &NameCulture) -> String {
+        let mut name = String::new();
+        for _ in 0..16 {
+            name = Self::compose(rng, culture);
+            if !self.used.contains(&name) {
+                return self.claim(name);
+            }
+        }
+
+        // Tables too small for the galaxy: number the repeats instead.
+        let mut index = 2;
+        while self.used.contains(&format!("{} {}", name, index)) {
+            index += 1;
+        }
+        self.claim(format!("{} {}", name, index))
+    }
+
+    /// Catalogue entry such as `OR3-G0042`: sector code, spectral letter and
+    /// a running number within that sector.
+    pub fn designation(&mut self, sector: &str, star_type: StarType) -> String {
+        let counter = self
+            .catalogue_counters
+            .entry(sector.to_string())
+            .or_default();
+        *counter += 1;
+        let designation = format!("{}-{}{:04}", sector, star_type.letter(), counter);
+        self.claim(designation)
+    }
+
+    fn compose(rng: &mut impl Rng, culture: &NameCulture) -> String {
+        let count = rng
+            .random_range(culture.min_syllables..=culture.max_syllables.max(culture.min_syllables));
+        let mut name = (0..count)
+            .filter_map(|_| culture.syllables.choose(rng))
+            .cloned()
+            .collect::<String>();
+        if let Some(ending) = culture.endings.choose(rng) {
+            name.push_str(ending);
+        }
+
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => "Unnamed".to_string(),
+        }
+    }
+
+    fn claim(&mut self, name: String) -> String {
+        self.used.insert(name.clone());
+        name
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use vecmath::Vector3;
+
+/// Uniform bucket grid for fixed-radius neighbour lookups. Stores indices
+/// into a position list kept by the caller. Cells are columns through the
+/// disk, so lookups ignore height and callers compare full 3D distances.
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size: cell_size.max(f32::EPSILON),
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn from_positions(positions: &[Vector3<f32>], cell_size: f32) -> Self {
+        let mut grid = Self::new(cell_size);
+        for (i, &position) in positions.iter().enumerate() {
+            grid.insert(i, position);
+        }
+        grid
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vector3<f32>) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push(index);
+    }
+
+    fn cell(&self, position: Vector3<f32>) -> (i32, i32) {
+        (
+            (position[0] / self.cell_size).floor() as i32,
+            (position[1] / self.cell_size).floor() as i32,
+        )
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Indices in the cell of `position` and the eight cells around it, which
+    /// covers everything within `cell_size` of it.
+    pub fn near(&self, position: Vector3<f32>) -> impl Iterator<Item = usize> + '_ {
+        self.within(position, self.cell_size)
+    }
+
+    /// Indices in every cell that can hold something within `radius` of
+    /// `position`.
+    pub fn within(&self, position: Vector3<f32>, radius: f32) -> impl Iterator<Item = usize> + '_ {
+        let reach = (radius / self.cell_size).ceil() as i32;
+        let (cx, cy) = self.cell(position);
+        (-reach..=reach)
+            .flat_map(move |dx| (-reach..=reach).map(move |dy| (cx + dx, cy + dy)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    /// Indices in the cells exactly `ring` cells away from the cell of
+    /// `position`. Everything beyond ring `k` is at least `k * cell_size`
+    /// away horizontally.
+    pub fn ring(&self, position: Vector3<f32>, ring: i32) -> impl Iterator<Item = usize> + '_ {
+        let (cx, cy) = self.cell(position);
+        let rows = (-ring..=ring).flat_map(move |dx| [(dx, -ring), (dx, ring)]);
+        let columns = (1 - ring..ring).flat_map(move |dy| [(-ring, dy), (ring, dy)]);
+        rows.chain(columns)
+            .take(if ring == 0 { 1 } else { 8 * ring as usize })
+            .filter_map(move |(dx, dy)| self.cells.get(&(cx + dx, cy + dy)))
+            .flatten()
+            .copied()
+    }
+}
@@ -1,152 +1,1123 @@
 use core::f64;
 
-use bevy::prelude::{BuildChildren, ChildBuild, Commands, Entity, Query, Res, With};
+use bevy::prelude::{
+    BuildChildren, ChildBuild, Commands, DetectChanges, Entity, Event, EventReader, EventWriter,
+    Local, Query, Res, With,
+};
 use itertools::Itertools;
-use rand::Rng;
-use rand_distr::Distribution;
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+use rand_distr::{Beta, Distribution, Exp1};
 
 use crate::game::{
     components::{
-        common::{Mass, Radius, ID},
-        orbit::{OrbitBundle, OrbitPeriod, OrbitPosition, OrbitRadius, Parent},
-        planet::{BodyType, BodyTypes, CelestialBodyBundle, SystemId},
-        star::Star,
+        common::{Mass, PendingGeneration, Radius, SystemSeed, ID},
+        orbit::{OrbitBundle, OrbitPeriod, OrbitalElements, Parent, AU, SOLAR_MASS},
+        planet::{
+            insolation, planet_radius, Annulus, BodyType, BodyTypes, CelestialBodyBundle,
+            EnvironmentBundle, EquilibriumTemperature, HeavyElements, SystemId, WaterFraction,
+            EARTH_MASS, GAS_GIANT_MASS,
+        },
+        star::{Companion, Luminosity, StarType, SystemCompanion, SystemStar},
+    },
+    resourses::{
+        evolution::EvolutionConfig,
+        system::{SelectedSystem, SystemGenConfig},
     },
-    resourses::system::SystemGenConfig,
+    systems::stellar_evolution::{supernova_victims, Supernova},
 };
 
-pub fn generate_plantary_systems(
+/// Asks for a star's planets to exist, e.g. when its system is scanned or
+/// visited.
+#[derive(Event)]
+pub struct GenerateSystem(pub ID);
+
+/// Gives pending stars their planets once they are asked for, lit by the
+/// stars as they shine now and without what their supernovae destroyed.
+pub fn generate_pending_systems(
     mut commands: Commands,
     config: Res<SystemGenConfig>,
-    star_query: Query<(Entity, &ID), With<Star>>,
+    evolution: Res<EvolutionConfig>,
+    mut requests: EventReader<GenerateSystem>,
+    stars: Query<(Entity, &ID, &SystemSeed, &Luminosity, &PendingGeneration)>,
+    companions: Query<(&SystemId, &ID, &Luminosity), With<Companion>>,
 ) {
-    let mut rng = rand::rng();
+    let wanted = requests.read().map(|r| r.0.clone()).collect::<Vec<_>>();
+    if wanted.is_empty() {
+        return;
+    }
+
+    for (entity, id, seed, luminosity, pending) in stars.iter() {
+        if !wanted.contains(id) {
+            continue;
+        }
+        let mut bodies =
+            generate_planetary_system(&config, &pending.star, &pending.companions, *seed);
 
-    star_query.iter().for_each(|(star_entity, id)| {
-        let num_bodies = rng.random_range(config.min_bodies..=config.max_bodies);
-        let mut bodies = (0..num_bodies)
-            .map(|_| generate_celestial_body(&mut rng, &config, id.clone()))
+        let star = SystemStar {
+            luminosity: luminosity.0,
+            ..pending.star.clone()
+        };
+        let current = pending
+            .companions
+            .iter()
+            .map(|companion| SystemCompanion {
+                luminosity: companions
+                    .iter()
+                    .find(|(system, star, _)| system.0 == *id && **star == companion.id)
+                    .map_or(companion.luminosity, |(.., luminosity)| luminosity.0),
+                ..companion.clone()
+            })
             .collect::<Vec<_>>();
+        set_equilibrium_temperatures(&mut bodies, &star, &current);
+        set_environments(&mut bodies);
+
+        for exploded in pending.exploded.iter() {
+            let supernova = Supernova {
+                star: exploded.clone(),
+                system: id.clone(),
+            };
+            let victims = supernova_victims(
+                &bodies
+                    .iter()
+                    .map(|body| {
+                        (
+                            &body.id,
+                            &body.orbit_bundle.parent,
+                            &body.orbit_bundle.elements,
+                            body.body_type.0,
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+                &supernova,
+                evolution.supernova_destruction_radius * AU,
+            );
+            bodies.retain(|body| !victims.contains(&body.id));
+        }
+        commands
+            .entity(entity)
+            .remove::<PendingGeneration>()
+            .with_children(|parent| {
+                for body in bodies {
+                    parent.spawn(body);
+                }
+            });
+    }
+}
 
-        bodies.sort_unstable_by(|a, b| b.mass.0.total_cmp(&a.mass.0));
+/// Asks for the selected system's planets whenever the selection moves to
+/// another star.
+pub fn request_selected_system(
+    selected: Res<SelectedSystem>,
+    mut last: Local<Option<ID>>,
+    mut requests: EventWriter<GenerateSystem>,
+) {
+    if !selected.is_changed() || *last == selected.0 {
+        return;
+    }
+    *last = selected.0.clone();
+    if let Some(id) = selected.0.clone() {
+        requests.send(GenerateSystem(id));
+    }
+}
+
+/// Bodies of one star's planetary system, fully determined by its seed and
+/// the stars it is made around.
+pub fn generate_planetary_system(
+    config: &SystemGenConfig,
+    star: &SystemStar,
+    companions: &[SystemCompanion],
+    seed: SystemSeed,
+) -> Vec<CelestialBodyBundle> {
+    let mut rng = StdRng::seed_from_u64(seed.0);
+    let star_id = &star.id;
+    let mass = star.mass as f64;
+    let metallicity = star.metallicity as f64;
+    let frost_line = star.frost_line;
 
-        assign_moons(&mut bodies, &config);
+    let mut bodies = match star.star_type {
+        StarType::WhiteDwarf => {
+            white_dwarf_system(&mut rng, config, star_id, mass, metallicity, frost_line)
+        }
+        StarType::Pulsar => pulsar_system(&mut rng, config, star_id),
+        StarType::NeutronStar | StarType::BlackHole => {
+            debris_disk(&mut rng, config, star_id).into_iter().collect()
+        }
+        _ => main_sequence_system(&mut rng, config, star_id, mass, metallicity, frost_line),
+    };
 
-        resolve_roche_limits(&mut bodies, &config);
+    if !companions.is_empty() {
+        fit_around_companions(&mut rng, &mut bodies, star, companions);
+    }
+    set_equilibrium_temperatures(&mut bodies, star, companions);
+    set_environments(&mut bodies);
+    set_orbit_periods(&mut bodies, star, companions);
+    bodies
+}
 
-        commands.entity(star_entity).with_children(|parent| {
-            for body in bodies {
-                parent.spawn(body);
+/// Derives every body's environment from its mass, water and equilibrium
+/// temperature, so temperatures must be set first.
+pub fn set_environments(bodies: &mut [CelestialBodyBundle]) {
+    for body in bodies.iter_mut() {
+        body.environment = EnvironmentBundle::derive(
+            body.mass.0,
+            body.radius.0,
+            body.water.0,
+            body.equilibrium_temperature.0,
+        );
+    }
+}
+
+/// Kepler periods around the primary, the companion or the body each
+/// orbit belongs to. Orbits around the primary circle the companions
+/// inside them as well, close pairs of those included.
+pub fn set_orbit_periods(
+    bodies: &mut [CelestialBodyBundle],
+    primary: &SystemStar,
+    companions: &[SystemCompanion],
+) {
+    let inner_mass = |distance: f64| {
+        companions
+            .iter()
+            .filter(|c| {
+                let direct = c
+                    .parent
+                    .as_ref()
+                    .and_then(|parent| companions.iter().find(|host| &host.id == parent))
+                    .unwrap_or(c);
+                direct.parent.is_none() && direct.separation < distance
+            })
+            .map(|c| c.mass as f64)
+            .sum::<f64>()
+    };
+
+    let periods = bodies
+        .iter()
+        .map(|body| {
+            let orbit = &body.orbit_bundle;
+            let own_mass = body.mass.0 as f64 / SOLAR_MASS;
+            let host_mass = match orbit.parent.0.as_ref() {
+                None => primary.mass as f64 + inner_mass(orbit.elements.semi_major_axis),
+                Some(parent) => match companions.iter().find(|c| &c.id == parent) {
+                    Some(companion) => companion.mass as f64,
+                    None => bodies
+                        .iter()
+                        .find(|b| &b.id == parent)
+                        .map_or(0.0, |planet| planet.mass.0 as f64 / SOLAR_MASS),
+                },
+            };
+            OrbitPeriod::kepler(orbit.elements.semi_major_axis, host_mass + own_mass)
+        })
+        .collect::<Vec<_>>();
+    for (body, period) in bodies.iter_mut().zip(periods) {
+        body.orbit_bundle.orbit_period = period;
+    }
+}
+
+/// Warms every body by the stars it orbits. Moons share their planet's
+/// light, and an orbit around the primary also gets the light of the
+/// companions circling inside it.
+pub fn set_equilibrium_temperatures(
+    bodies: &mut [CelestialBodyBundle],
+    primary: &SystemStar,
+    companions: &[SystemCompanion],
+) {
+    let received = |orbit: &OrbitBundle| {
+        let distance = orbit.elements.semi_major_axis;
+        match orbit.parent.0.as_ref() {
+            Some(parent) => companions
+                .iter()
+                .find(|c| &c.id == parent)
+                .map_or(0.0, |host| insolation(host.luminosity, distance)),
+            None => {
+                let inner = companions
+                    .iter()
+                    .filter(|c| c.parent.is_none())
+                    .filter(|c| c.separation < distance)
+                    .map(|c| c.luminosity)
+                    .sum::<f32>();
+                insolation(primary.luminosity + inner, distance)
             }
-        });
-    })
+        }
+    };
+
+    let temperatures = bodies
+        .iter()
+        .map(|body| {
+            let planet = body
+                .orbit_bundle
+                .parent
+                .0
+                .as_ref()
+                .and_then(|parent| bodies.iter().find(|b| &b.id == parent))
+                .unwrap_or(body);
+            EquilibriumTemperature::from_insolation(received(&planet.orbit_bundle))
+        })
+        .collect::<Vec<_>>();
+    for (body, temperature) in bodies.iter_mut().zip(temperatures) {
+        body.equilibrium_temperature = temperature;
+    }
 }
 
+/// Largest stable S-type orbit around a star with a companion at
+/// `separation` and eccentricity `e`, where `mu` is the companion's share of
+/// the pair's mass (Holman & Wiegert 1999).
+fn s_type_limit(separation: f64, e: f64, mu: f64) -> f64 {
+    separation
+        * (0.464 - 0.380 * mu - 0.631 * e + 0.586 * mu * e + 0.150 * e * e - 0.198 * mu * e * e)
+}
+
+/// Smallest stable P-type orbit around a pair, same terms as `s_type_limit`.
+fn p_type_limit(separation: f64, e: f64, mu: f64) -> f64 {
+    separation
+        * (1.60 + 5.10 * e - 2.22 * e * e + 4.12 * mu - 4.27 * e * mu - 5.09 * mu * mu
+            + 4.61 * e * e * mu * mu)
+}
+
+/// Smallest orbit worth moving a planet to around a companion.
+static MIN_COMPANION_ORBIT: f64 = 0.02;
+
+/// Keeps planets of the primary in its stable zones: S-type inside its
+/// nearest companion, P-type around each inner group of stars. Planets in
+/// between move to an S-type orbit around a companion that has room, or are
+/// lost together with their moons.
+fn fit_around_companions(
+    rng: &mut impl Rng,
+    bodies: &mut Vec<CelestialBodyBundle>,
+    primary: &SystemStar,
+    companions: &[SystemCompanion],
+) {
+    let separation = |c: &SystemCompanion| c.separation;
+
+    let mut direct = companions
+        .iter()
+        .filter(|c| c.parent.is_none())
+        .collect::<Vec<_>>();
+    direct.sort_by(|a, b| separation(a).total_cmp(&separation(b)));
+
+    // Stable bands around the primary, and the room each companion has.
+    let mut zones = Vec::new();
+    let mut limits = Vec::new();
+    let mut inner_mass = primary.mass as f64;
+    let mut lower = 0.0;
+    for companion in direct.iter() {
+        let mass = companion.mass as f64;
+        let mu = mass / (inner_mass + mass);
+        let e = companion.eccentricity;
+        zones.push((lower, s_type_limit(separation(companion), e, mu)));
+        lower = p_type_limit(separation(companion), e, mu);
+        limits.push((*companion, s_type_limit(separation(companion), e, 1.0 - mu)));
+        inner_mass += mass;
+    }
+    zones.push((lower, f64::INFINITY));
+
+    // Close pairs around a companion squeeze both of its members.
+    for companion in companions.iter() {
+        let Some(parent) = companion.parent.as_ref() else {
+            continue;
+        };
+        let Some(host) = companions.iter().find(|c| &c.id == parent) else {
+            continue;
+        };
+        let mu = companion.mass as f64 / (host.mass + companion.mass) as f64;
+        let e = companion.eccentricity;
+        for (star, limit) in limits.iter_mut() {
+            if star.id == host.id {
+                *limit = limit.min(s_type_limit(separation(companion), e, mu));
+            }
+        }
+        limits.push((companion, s_type_limit(separation(companion), e, 1.0 - mu)));
+    }
+    let hosts = limits
+        .into_iter()
+        .filter(|(_, limit)| *limit > MIN_COMPANION_ORBIT * AU)
+        .collect::<Vec<_>>();
+
+    let mut lost = Vec::new();
+    for body in bodies.iter_mut() {
+        if body.orbit_bundle.parent.0.is_some() {
+            continue;
+        }
+        let (closest, furthest) = body.extent();
+        if zones
+            .iter()
+            .any(|&(low, high)| low < closest && furthest < high)
+        {
+            continue;
+        }
+        // A belt cut by a companion's orbit is scattered rather than moved.
+        if body.annulus.is_band() {
+            lost.push(body.id.clone());
+            continue;
+        }
+
+        match hosts.choose(rng) {
+            Some((host, limit)) => {
+                let orbit = rng.random_range((MIN_COMPANION_ORBIT * AU).ln()..limit.ln());
+                body.orbit_bundle.elements.semi_major_axis = orbit.exp();
+                body.orbit_bundle.elements.eccentricity = 0.0;
+                body.orbit_bundle.parent.0 = Some(host.id.clone());
+            }
+            None => lost.push(body.id.clone()),
+        }
+    }
+
+    remove_with_moons(bodies, &lost);
+}
+
+fn remove_with_moons(bodies: &mut Vec<CelestialBodyBundle>, lost: &[ID]) {
+    bodies.retain(|b| {
+        !lost.contains(&b.id)
+            && !b
+                .orbit_bundle
+                .parent
+                .0
+                .as_ref()
+                .is_some_and(|parent| lost.contains(parent))
+    });
+}
+
+/// `star_mass` is in solar masses.
+fn main_sequence_system(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    star_id: &ID,
+    star_mass: f64,
+    metallicity: f64,
+    frost_line: f64,
+) -> Vec<CelestialBodyBundle> {
+    // Metal-rich systems have more solids to build planets from.
+    let richness = 10f64.powf(config.metallicity_body_factor * metallicity);
+    let num_bodies = (rng.random_range(config.min_bodies..=config.max_bodies) as f64 * richness)
+        .round()
+        .clamp(config.min_bodies as f64, 2.0 * config.max_bodies as f64)
+        as usize;
+
+    let mut bodies = place_planets(
+        rng,
+        config,
+        star_id,
+        star_mass,
+        metallicity,
+        frost_line,
+        num_bodies,
+    );
+    // Drawn where a planet is, so ice makes them as heavy as it would there.
+    let orbits = bodies
+        .iter()
+        .map(|b| b.orbit_bundle.elements.semi_major_axis)
+        .collect::<Vec<_>>();
+    let leftovers = (bodies.len()..num_bodies)
+        .filter_map(|_| {
+            let orbit = *orbits.choose(rng)?;
+            let body = generate_celestial_body(
+                rng,
+                config,
+                star_id.clone(),
+                metallicity,
+                frost_line,
+                orbit,
+            );
+            Some(body)
+        })
+        .collect::<Vec<_>>();
+    assign_moons(rng, config, &mut bodies, leftovers, star_mass);
+
+    resolve_roche_limits(&mut bodies, config);
+
+    add_rings(rng, config, &mut bodies);
+
+    add_belts(rng, config, &mut bodies, star_id, metallicity, frost_line);
+
+    bodies
+}
+
+/// Semi-major axis ratio of an orbit whose period is `ratio` times the
+/// other's.
+fn resonance(ratio: f64) -> f64 {
+    ratio.powf(2.0 / 3.0)
+}
+
+/// Belts where giants stir up planetesimals too much for planets to form:
+/// between the 4:1 and 2:1 resonances inside the innermost giant, or just
+/// past the frost line without one, and between the 3:2 and 2:1 resonances
+/// outside the outermost giant. Planets inside a belt are cleared out.
+fn add_belts(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    bodies: &mut Vec<CelestialBodyBundle>,
+    star_id: &ID,
+    metallicity: f64,
+    frost_line: f64,
+) {
+    let giants = bodies
+        .iter()
+        .filter(|b| b.orbit_bundle.parent.0.is_none() && is_gas_giant(b.mass.0))
+        .map(|b| b.orbit_bundle.elements.semi_major_axis)
+        .collect::<Vec<_>>();
+    let innermost = giants.iter().copied().min_by(f64::total_cmp);
+    let outermost = giants.iter().copied().max_by(f64::total_cmp);
+
+    // Rocky inner belts are the metal-rich ones, outer belts are mostly ice.
+    let mut belts = Vec::new();
+    if rng.random_bool(config.inner_belt_chance) {
+        let edges = match innermost {
+            Some(giant) => (giant / resonance(4.0), giant / resonance(2.0)),
+            None => (frost_line, frost_line * resonance(2.0)),
+        };
+        belts.push((edges, 2.0));
+    }
+    if let Some(giant) = outermost {
+        if rng.random_bool(config.outer_belt_chance) {
+            belts.push(((giant * resonance(1.5), giant * resonance(2.0)), 0.5));
+        }
+    }
+
+    let (min_mass, max_mass) = (config.min_belt_mass.log10(), config.max_belt_mass.log10());
+    for ((inner, outer), richness) in belts {
+        let lost = bodies
+            .iter()
+            .filter(|b| b.orbit_bundle.parent.0.is_none())
+            .filter(|b| (inner..outer).contains(&b.orbit_bundle.elements.semi_major_axis))
+            .map(|b| b.id.clone())
+            .collect::<Vec<_>>();
+        remove_with_moons(bodies, &lost);
+
+        let mass = 10f64.powf(rng.random_range(min_mass..max_mass) + metallicity) as f32;
+        let deposits = rand_distr::LogNormal::new(0.0, 0.3).unwrap().sample(rng) as f32
+            * 10f32.powf(metallicity as f32)
+            * richness;
+        let mut belt = body(rng, star_id.clone(), mass, (inner + outer) / 2.0, deposits);
+        belt.body_type = BodyType(BodyTypes::AsteroidBelt);
+        belt.annulus = Annulus { inner, outer };
+        bodies.push(belt);
+    }
+}
+
+/// Ring systems around gas giants that have none yet, from just above the
+/// cloud tops out to the Roche limit for ice.
+fn add_rings(rng: &mut impl Rng, config: &SystemGenConfig, bodies: &mut Vec<CelestialBodyBundle>) {
+    let ringed = |id: &ID| {
+        bodies.iter().any(|b| {
+            matches!(b.body_type.0, BodyTypes::Ring) && b.orbit_bundle.parent.0.as_ref() == Some(id)
+        })
+    };
+    let giants = bodies
+        .iter()
+        .filter(|b| matches!(b.body_type.0, BodyTypes::GasGiant) && !ringed(&b.id))
+        .map(|b| (b.id.clone(), b.system_id.0.clone(), b.mass.0, b.radius.0))
+        .collect::<Vec<_>>();
+
+    for (id, system, mass, radius) in giants {
+        if !rng.random_bool(config.ring_chance) {
+            continue;
+        }
+        let radius = radius as f64 * 1000.0;
+        let density = mass as f64 / (4.0 / 3.0 * f64::consts::PI * radius.powi(3));
+        let inner = radius * rng.random_range(1.1..1.5);
+        let outer = (2.44 * radius * (density / ICE_DENSITY).cbrt()).max(inner * 1.2);
+
+        let ring_mass = mass * 10f32.powf(rng.random_range(-9.0..-7.0));
+        let mut ring = body(rng, system, ring_mass, (inner + outer) / 2.0, 1.0);
+        ring.body_type = BodyType(BodyTypes::Ring);
+        ring.annulus = Annulus { inner, outer };
+        ring.orbit_bundle.parent.0 = Some(id);
+        bodies.push(ring);
+    }
+}
+
+/// In kg per cubic meter.
+static ICE_DENSITY: f64 = 900.0;
+
+/// Survivors of the red giant phase, plus dust from whatever strayed too close since.
+fn white_dwarf_system(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    star_id: &ID,
+    star_mass: f64,
+    metallicity: f64,
+    frost_line: f64,
+) -> Vec<CelestialBodyBundle> {
+    let engulfed = config.white_dwarf_engulfment_radius * AU;
+    let mut bodies = main_sequence_system(rng, config, star_id, star_mass, metallicity, frost_line);
+
+    let lost = bodies
+        .iter()
+        .filter(|b| {
+            b.orbit_bundle.parent.0.is_none() && b.orbit_bundle.elements.periapsis() < engulfed
+        })
+        .map(|b| b.id.clone())
+        .collect::<Vec<_>>();
+    remove_with_moons(&mut bodies, &lost);
+
+    bodies.extend(debris_disk(rng, config, star_id));
+    bodies
+}
+
+/// A few small, metal-rich worlds condensed from supernova fallback.
+fn pulsar_system(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    star_id: &ID,
+) -> Vec<CelestialBodyBundle> {
+    let mut bodies = Vec::new();
+    if rng.random_bool(config.pulsar_planet_chance) {
+        for _ in 0..rng.random_range(1..=3) {
+            let mass = rng.random_range(0.02..5.0) * EARTH_MASS;
+            let orbit_radius = rng.random_range(0.1..0.6) * AU;
+            let mut planet = body(rng, star_id.clone(), mass, orbit_radius, 5.0);
+            shape_orbit(rng, config, &mut planet.orbit_bundle.elements, 1.0);
+            bodies.push(planet);
+        }
+    }
+    bodies.extend(debris_disk(rng, config, star_id));
+    bodies
+}
+
+/// Tight ring of rubble left by a remnant shredding its companions.
+fn debris_disk(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    star_id: &ID,
+) -> Option<CelestialBodyBundle> {
+    if !rng.random_bool(config.debris_disk_chance) {
+        return None;
+    }
+    let mass = 10f32.powf(rng.random_range(19.0..22.0));
+    let orbit_radius = rng.random_range(0.005..0.05) * AU;
+    // Circular, it is a ring of rubble rather than a single orbit.
+    let mut disk = body(rng, star_id.clone(), mass, orbit_radius, 8.0);
+    disk.body_type = BodyType(BodyTypes::AsteroidBelt);
+    disk.annulus = Annulus {
+        inner: orbit_radius * 0.7,
+        outer: orbit_radius * 1.3,
+    };
+    Some(disk)
+}
+
+/// Planets from the inside out, at most `count` of them. Each orbit is a
+/// period ratio away from the one inside it, pushed further out when needed
+/// so the gap between apoapsis and periapsis spans enough mutual Hill radii.
+/// The system reaches out in proportion to the star's mass.
+fn place_planets(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    star_id: &ID,
+    star_mass: f64,
+    metallicity: f64,
+    frost_line: f64,
+    count: usize,
+) -> Vec<CelestialBodyBundle> {
+    let scale = star_mass.powf(config.extent_mass_exponent) * AU;
+    let outer_edge = config.outer_orbit * scale;
+    let innermost = rng.random_range(config.min_inner_orbit.ln()..config.max_inner_orbit.ln());
+    let mut orbit = innermost.exp() * scale;
+
+    let mut planets = Vec::<CelestialBodyBundle>::new();
+    while planets.len() < count && orbit < outer_edge {
+        let mut planet =
+            generate_celestial_body(rng, config, star_id.clone(), metallicity, frost_line, orbit);
+
+        let mut fits = true;
+        if let Some(inner) = planets.last() {
+            let elements = &mut planet.orbit_bundle.elements;
+            let inner_elements = &inner.orbit_bundle.elements;
+            let hill_factor = config.min_hill_spacing
+                * ((inner.mass.0 + planet.mass.0) as f64 / (3.0 * star_mass * SOLAR_MASS)).cbrt();
+            // Keeps the outer periapsis clear of the inner apoapsis.
+            elements.eccentricity = elements.eccentricity.min(0.8 - hill_factor / 2.0).max(0.0);
+            let room = 1.0 - elements.eccentricity - hill_factor / 2.0;
+            // The mutual Hill radius then grows faster than the gap, no orbit
+            // is far enough out and the draw is dropped.
+            fits = room > 0.0;
+            if fits {
+                let spaced = inner_elements.semi_major_axis
+                    * (1.0 + inner_elements.eccentricity + hill_factor / 2.0)
+                    / room;
+                elements.semi_major_axis = elements.semi_major_axis.max(spaced);
+            }
+        }
+        if planet.orbit_bundle.elements.semi_major_axis > outer_edge {
+            break;
+        }
+
+        let ratio = rng.random_range(config.min_period_ratio.ln()..config.max_period_ratio.ln());
+        orbit = planet.orbit_bundle.elements.semi_major_axis * resonance(ratio.exp());
+        if fits {
+            planets.push(planet);
+        }
+    }
+    planets
+}
+
+/// Body drawn for an orbit of `orbit_radius` meters. Metals and ice feed
+/// the solid core, cores heavy enough grow into giants. Giants drawn inside
+/// the frost line move out past it unless they count as migrated.
 fn generate_celestial_body(
     rng: &mut impl Rng,
     config: &SystemGenConfig,
     star_id: ID,
+    metallicity: f64,
+    frost_line: f64,
+    orbit_radius: f64,
+) -> CelestialBodyBundle {
+    let mut orbit_radius = orbit_radius;
+
+    // Ice beyond the frost line gives cores more solids to grow from.
+    let log_mean_mass =
+        config.log_mean_mass + config.metallicity_mass_factor * metallicity * f64::consts::LN_10;
+    let log_normal = rand_distr::LogNormal::new(log_mean_mass, config.log_std_mass).unwrap();
+    let mut core_mass = log_normal.sample(rng);
+    let icy = orbit_radius > frost_line;
+    if icy {
+        core_mass *= config.frost_line_mass_factor;
+    }
+    let mass = if core_mass > config.giant_core_mass {
+        let log_mass = rng.random_range(config.min_giant_mass.ln()..config.max_giant_mass.ln());
+        log_mass.exp() as f32 * EARTH_MASS
+    } else {
+        core_mass as f32 * EARTH_MASS
+    };
+
+    // Giants form past the frost line, only a few migrate inwards.
+    if is_gas_giant(mass)
+        && orbit_radius < frost_line
+        && !rng.random_bool(config.inner_giant_chance)
+    {
+        orbit_radius = frost_line * rng.random_range(1.0..1.5);
+    }
+
+    // Gas giants keep their metals deep in the envelope, out of reach.
+    let deposits = rand_distr::LogNormal::new(0.0, 0.3).unwrap().sample(rng) as f32
+        * 10f32.powf(metallicity as f32)
+        * if is_gas_giant(mass) { 0.1 } else { 1.0 };
+
+    let mut planet = body(rng, star_id, mass, orbit_radius, deposits);
+    planet.water = formation_water(rng, icy);
+    shape_orbit(rng, config, &mut planet.orbit_bundle.elements, 1.0);
+    planet
+}
+
+/// Bodies formed past the frost line are up to half water, those inside
+/// it hold only traces.
+fn formation_water(rng: &mut impl Rng, icy: bool) -> WaterFraction {
+    WaterFraction(if icy {
+        rng.random_range(0.1..0.5)
+    } else {
+        10f32.powf(rng.random_range(-4.0..-2.0))
+    })
+}
+
+/// Draws eccentricity, inclination and orientation for a circular orbit,
+/// eccentricity scaled by `eccentricity_factor`.
+fn shape_orbit(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    elements: &mut OrbitalElements,
+    eccentricity_factor: f64,
+) {
+    let beta = Beta::new(config.eccentricity_alpha, config.eccentricity_beta).unwrap();
+    elements.eccentricity = (beta.sample(rng) * eccentricity_factor).min(0.95);
+    let rayleigh = (2.0 * rng.sample::<f64, _>(Exp1)).sqrt();
+    elements.inclination = (rayleigh * config.inclination_scale).to_radians();
+    elements.argument_of_periapsis = rng.random_range(0.0..f64::consts::TAU);
+    elements.longitude_of_ascending_node = rng.random_range(0.0..f64::consts::TAU);
+}
+
+/// Planet with a measured mass in kg and orbit radius in meters, around a
+/// star with its frost line at `frost_line` meters.
+pub fn known_planet(
+    rng: &mut impl Rng,
+    star_id: &ID,
+    mass: f32,
+    orbit_radius: f64,
+    frost_line: f64,
 ) -> CelestialBodyBundle {
-    let log_normal = rand_distr::LogNormal::new(config.log_mean_mass, config.log_std_mass).unwrap();
-    let mass = log_normal.sample(rng) as f32 * 5.976e24;
+    let deposits = if is_gas_giant(mass) { 0.1 } else { 1.0 };
+    let mut planet = body(rng, star_id.clone(), mass, orbit_radius, deposits);
+    planet.water = formation_water(rng, orbit_radius > frost_line);
+    planet
+}
 
+fn body(
+    rng: &mut impl Rng,
+    star_id: ID,
+    mass: f32,
+    orbit_radius: f64,
+    deposits: f32,
+) -> CelestialBodyBundle {
     CelestialBodyBundle {
-        id: ID::default(),
+        id: ID::from_rng(rng),
         system_id: SystemId(star_id),
         mass: Mass(mass),
-        radius: Radius(mass.powf(0.3) * 6371.0),
+        // In km.
+        radius: Radius(planet_radius(mass)),
         orbit_bundle: OrbitBundle {
-            orbit_radius: OrbitRadius(rng.random_range(0.1..1500.0) * 149597870700.0),
-            orbit_position: OrbitPosition(rng.random_range(0.0..f64::consts::TAU)),
+            elements: OrbitalElements::circular(
+                orbit_radius,
+                rng.random_range(0.0..f64::consts::TAU),
+            ),
             orbit_period: OrbitPeriod(0.0),
             parent: Parent(None),
         },
-        body_type: BodyType(if mass > 0.1 * 1.898e27 {
+        body_type: BodyType(if is_gas_giant(mass) {
             BodyTypes::GasGiant
         } else {
             BodyTypes::Planet
         }),
+        heavy_elements: HeavyElements(deposits),
+        equilibrium_temperature: EquilibriumTemperature::default(),
+        water: WaterFraction::default(),
+        annulus: Annulus::default(),
+        environment: EnvironmentBundle::default(),
     }
 }
 
-static SOLAR_MASS: f64 = 1.989e30;
-
-fn assign_moons(bodies: &mut [CelestialBodyBundle], config: &SystemGenConfig) {
-    let mut rng = rand::rng();
-    let mut moon_assignments = Vec::new();
-
-    // Первый проход: собираем информацию о лунах
-    for i in 0..bodies.len() {
-        let parent = &bodies[i];
-
-        let hill_radius = parent.orbit_bundle.orbit_radius.0
-            * (parent.mass.0 as f64 / (3.0 * SOLAR_MASS)).powf(1.0 / 3.0);
-
-        // Проверяем последующие тела
-        for j in (i + 1)..bodies.len() {
-            let child = &bodies[j];
-            if child.orbit_bundle.parent.0.is_some() {
-                continue;
-            }
+fn is_gas_giant(mass: f32) -> bool {
+    mass > GAS_GIANT_MASS
+}
 
-            let distance =
-                (child.orbit_bundle.orbit_radius.0 - parent.orbit_bundle.orbit_radius.0).abs();
-            if distance < hill_radius && child.mass.0 < parent.mass.0 {
-                moon_assignments.push((i, j, distance));
-            }
-        }
+/// Turns the bodies that found no planetary orbit into moons of heavier
+/// planets, well inside their Hill spheres. Bodies heavier than the planet
+/// they were drawn for, or with no room between its Roche limit and Hill
+/// sphere, are dropped.
+fn assign_moons(
+    rng: &mut impl Rng,
+    config: &SystemGenConfig,
+    bodies: &mut Vec<CelestialBodyBundle>,
+    leftovers: Vec<CelestialBodyBundle>,
+    star_mass: f64,
+) {
+    let planets = bodies.len();
+    if planets == 0 {
+        return;
     }
+    for mut moon in leftovers {
+        let parent = &bodies[rng.random_range(0..planets)];
+        if moon.mass.0 >= parent.mass.0 {
+            continue;
+        }
+        let hill_radius = parent.orbit_bundle.elements.periapsis()
+            * (parent.mass.0 as f64 / (3.0 * star_mass * SOLAR_MASS)).cbrt();
+        let Some(distance) =
+            generate_moon_orbit(rng, parent, &moon, config.moon_hill_fraction * hill_radius)
+        else {
+            continue;
+        };
 
-    // Второй проход: применяем изменения
-    for (parent_idx, child_idx, distance) in moon_assignments {
-        let parent_entity = bodies[parent_idx].id.clone();
-        let parent_mass = bodies[parent_idx].mass;
-        let parent_radius = bodies[parent_idx].radius;
-
-        let child = &mut bodies[child_idx];
-        child.orbit_bundle.parent.0 = Some(parent_entity);
-        child.orbit_bundle.orbit_radius.0 =
-            generate_moon_orbit(&mut rng, parent_mass.0, parent_radius.0, distance);
+        moon.orbit_bundle.parent.0 = Some(parent.id.clone());
+        moon.body_type = BodyType(BodyTypes::Moon);
+        moon.orbit_bundle.elements.semi_major_axis = distance;
+        shape_orbit(
+            rng,
+            config,
+            &mut moon.orbit_bundle.elements,
+            config.moon_eccentricity_factor,
+        );
+        bodies.push(moon);
     }
 }
 
-// Обновленная функция генерации орбиты лун
+/// Orbit radius in meters for `moon` around `parent`, between the parent's
+/// Roche limit and `max_distance`. `None` when there is no room.
 fn generate_moon_orbit(
     rng: &mut impl Rng,
-    parent_mass: f32,
-    parent_radius: f32,
+    parent: &CelestialBodyBundle,
+    moon: &CelestialBodyBundle,
     max_distance: f64,
-) -> f64 {
-    let roche_limit =
-        2.44 * parent_radius as f64 * (parent_mass as f64 / SOLAR_MASS).powf(1.0 / 3.0);
+) -> Option<f64> {
+    let roche_limit = roche_limit(parent, moon);
+    (roche_limit < max_distance).then(|| rng.random_range(roche_limit..max_distance))
+}
 
-    if roche_limit >= max_distance {
-        return 0.0;
-    }
-    rng.random_range(roche_limit..max_distance)
+/// Distance from `parent` in meters inside which its tides tear `body`
+/// apart, for a fluid body.
+fn roche_limit(parent: &CelestialBodyBundle, body: &CelestialBodyBundle) -> f64 {
+    // Radii are in km, orbits in meters.
+    2.44 * parent.radius.0 as f64 * 1000.0 * ((parent.density() / body.density()) as f64).cbrt()
 }
 
 fn resolve_roche_limits(bodies: &mut Vec<CelestialBodyBundle>, config: &SystemGenConfig) {
     let mut to_remove = Vec::new();
-    let mut rng = rand::rng();
 
     for (i, body) in bodies.iter().enumerate() {
         if let Some(parent_entity) = body.orbit_bundle.parent.0.clone() {
             if let Some(parent) = bodies.iter().find(|b| b.id == parent_entity) {
-                let roche_limit =
-                    2.44 * parent.radius.0 * (parent.density() / body.density()).powf(1.0 / 3.0);
+                let surface = parent.radius.0 as f64 * 1000.0;
+                let roche_limit = roche_limit(parent, body);
 
-                if (body.orbit_bundle.orbit_radius.0 as f32)
-                    < roche_limit * config.roche_limit_factor as f32
+                if body.orbit_bundle.elements.periapsis() < roche_limit * config.roche_limit_factor
                 {
-                    to_remove.push(i);
+                    to_remove.push((i, surface, roche_limit));
                 }
             }
         }
     }
 
     // Удаляем или заменяем на кольца
-    for &i in to_remove.iter().rev() {
-        bodies[i].body_type.0 = BodyTypes::Ring;
+    for &(i, surface, roche_limit) in to_remove.iter().rev() {
+        let inner = surface * 1.1;
+        let outer = roche_limit.max(inner * 1.2);
+        let ring = &mut bodies[i];
+        ring.body_type.0 = BodyTypes::Ring;
+        ring.annulus = Annulus { inner, outer };
+        ring.orbit_bundle.elements = OrbitalElements::circular((inner + outer) / 2.0, 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn massive_planets_around_a_light_star_stay_spaced() {
+        let config = SystemGenConfig {
+            log_mean_mass: 1000f64.ln(),
+            log_std_mass: 0.5,
+            ..Default::default()
+        };
+        let star_mass = 0.08;
+        let star_id = ID("star".to_string());
+        let frost_line = 0.1 * AU;
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let planets =
+                place_planets(&mut rng, &config, &star_id, star_mass, 0.0, frost_line, 10);
+            for (inner, outer) in planets.iter().tuple_windows() {
+                let inner_orbit = &inner.orbit_bundle.elements;
+                let outer_orbit = &outer.orbit_bundle.elements;
+                let hill_radius =
+                    ((inner.mass.0 + outer.mass.0) as f64 / (3.0 * star_mass * SOLAR_MASS)).cbrt()
+                        * (inner_orbit.semi_major_axis + outer_orbit.semi_major_axis)
+                        / 2.0;
+                let gap = outer_orbit.periapsis() - inner_orbit.apoapsis();
+                assert!(
+                    gap >= config.min_hill_spacing * hill_radius * 0.999,
+                    "seed {}",
+                    seed
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn metallicity_feeds_cores_and_giants() {
+        let config = SystemGenConfig::default();
+        let frost_line = AU;
+        let draw = |metallicity: f64, orbit: f64| {
+            let mut rng = StdRng::seed_from_u64(3);
+            (0..4000)
+                .map(|_| {
+                    let star_id = ID("star".to_string());
+                    generate_celestial_body(
+                        &mut rng,
+                        &config,
+                        star_id,
+                        metallicity,
+                        frost_line,
+                        orbit,
+                    )
+                    .mass
+                    .0
+                })
+                .collect::<Vec<_>>()
+        };
+        let rocky_mean = |masses: &[f32]| {
+            let rocky = masses.iter().filter(|m| !is_gas_giant(**m));
+            rocky.clone().sum::<f32>() / rocky.count() as f32
+        };
+        let giant_share = |masses: &[f32]| {
+            masses.iter().filter(|m| is_gas_giant(**m)).count() as f32 / masses.len() as f32
+        };
+
+        let (poor, rich) = (draw(-1.0, 0.5 * AU), draw(0.5, 0.5 * AU));
+        assert!(rocky_mean(&rich) > 2.0 * rocky_mean(&poor));
+
+        let (poor, rich) = (draw(-1.0, 3.0 * AU), draw(0.5, 3.0 * AU));
+        assert!(rocky_mean(&rich) > 1.5 * rocky_mean(&poor));
+        assert!(giant_share(&poor) < 0.05);
+        assert!(giant_share(&rich) > 0.2);
+        for mass in rich.iter().filter(|m| is_gas_giant(**m)) {
+            let earth_masses = (mass / EARTH_MASS) as f64;
+            assert!((config.min_giant_mass..=config.max_giant_mass * 1.001).contains(&earth_masses));
+        }
+    }
+
+    #[test]
+    fn systems_do_not_depend_on_request_order() {
+        use crate::game::{
+            resourses::{
+                galaxy::GalaxyConfig, generation::GenerationProgress, naming::NamingConfig,
+            },
+            systems::galaxy_generation::generate_galaxy,
+        };
+
+        let config = SystemGenConfig::default();
+        let galaxy = GalaxyConfig {
+            num_stars: 40,
+            open_cluster_count: 0,
+            globular_cluster_count: 0,
+            nebula_count: 0,
+            ..Default::default()
+        };
+        let generate = |reverse: bool| {
+            let galaxy = generate_galaxy(
+                &galaxy,
+                &NamingConfig::default(),
+                &GenerationProgress::default(),
+            )
+            .unwrap();
+            let mut stars = galaxy
+                .stars
+                .iter()
+                .filter_map(|star| Some((star.seed, star.pending()?)))
+                .collect::<Vec<_>>();
+            if reverse {
+                stars.reverse();
+            }
+            stars
+                .iter()
+                .map(|(seed, pending)| {
+                    let bodies = generate_planetary_system(
+                        &config,
+                        &pending.star,
+                        &pending.companions,
+                        *seed,
+                    );
+                    let bodies = bodies
+                        .iter()
+                        .map(|body| {
+                            (
+                                body.id.0.clone(),
+                                body.mass.0,
+                                body.orbit_bundle.elements.semi_major_axis,
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (pending.star.id.0.clone(), bodies)
+                })
+                .sorted_by(|a, b| a.0.cmp(&b.0))
+                .collect::<Vec<_>>()
+        };
+
+        let (forward, reverse) = (generate(false), generate(true));
+        assert!(forward.iter().any(|(_, bodies)| !bodies.is_empty()));
+        assert!(forward == reverse);
+    }
+
+    #[test]
+    fn pending_systems_catch_up_with_their_star() {
+        use bevy::prelude::{App, Events, IntoSystemConfigs, Update};
+
+        use crate::game::{
+            components::{common::Name, spatial::Position, star::Metallicity, star::Star},
+            systems::stellar_evolution::{
+                apply_supernovae, rescale_equilibrium_temperatures, StarEvolved,
+            },
+        };
+
+        let config = SystemGenConfig::default();
+        let radius = EvolutionConfig::default().supernova_destruction_radius * AU;
+        let id = ID("star".to_string());
+        let star = SystemStar {
+            id: id.clone(),
+            star_type: StarType::G,
+            mass: 1.0,
+            luminosity: 1.0,
+            metallicity: 0.0,
+            frost_line: 2.7 * AU,
+        };
+        let (seed, reference) = (0..1000)
+            .map(|seed| {
+                (
+                    seed,
+                    generate_planetary_system(&config, &star, &[], SystemSeed(seed)),
+                )
+            })
+            .find(|(_, bodies)| {
+                let planets = bodies.iter().filter(|b| b.orbit_bundle.parent.0.is_none());
+                let inner =
+                    |body: &&CelestialBodyBundle| body.orbit_bundle.elements.periapsis() < radius;
+                planets.clone().any(|body| inner(&body))
+                    && !planets.clone().all(|body| inner(&body))
+            })
+            .unwrap();
+
+        let mut app = App::new();
+        app.insert_resource(config)
+            .insert_resource(EvolutionConfig::default())
+            .add_event::<StarEvolved>()
+            .add_event::<Supernova>()
+            .add_event::<GenerateSystem>()
+            .add_systems(
+                Update,
+                (
+                    rescale_equilibrium_temperatures,
+                    apply_supernovae,
+                    generate_pending_systems,
+                )
+                    .chain(),
+            );
+        app.world_mut().spawn((
+            Star,
+            id.clone(),
+            Name("Star".to_string()),
+            Position::default(),
+            Metallicity(0.0),
+            Luminosity(100.0),
+            SystemSeed(seed),
+            PendingGeneration {
+                star,
+                companions: Vec::new(),
+                exploded: Vec::new(),
+            },
+        ));
+        app.world_mut()
+            .resource_mut::<Events<StarEvolved>>()
+            .send(StarEvolved {
+                star: id.clone(),
+                system: id.clone(),
+                luminosity_ratio: 100.0,
+            });
+        app.world_mut()
+            .resource_mut::<Events<Supernova>>()
+            .send(Supernova {
+                star: id.clone(),
+                system: id.clone(),
+            });
+        app.update();
+        app.world_mut()
+            .resource_mut::<Events<GenerateSystem>>()
+            .send(GenerateSystem(id.clone()));
+        app.update();
+
+        let bodies = app
+            .world_mut()
+            .query::<(&ID, &Parent, &OrbitalElements, &EquilibriumTemperature)>()
+            .iter(app.world())
+            .map(|(id, parent, orbit, temperature)| {
+                (
+                    id.clone(),
+                    parent.0.clone(),
+                    orbit.periapsis(),
+                    temperature.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert!(!bodies.is_empty());
+        for (body, parent, periapsis, temperature) in bodies.iter() {
+            match parent {
+                None => assert!(*periapsis >= radius, "{}", body.0),
+                Some(parent) => {
+                    assert!(bodies.iter().any(|(id, ..)| id == parent), "{}", body.0)
+                }
+            }
+            let before = reference.iter().find(|b| &b.id == body).unwrap();
+            let expected = before.equilibrium_temperature.0 * 100f32.powf(0.25);
+            assert!(
+                (temperature - expected).abs() < 1e-3 * expected,
+                "{}",
+                body.0
+            );
+        }
     }
 }
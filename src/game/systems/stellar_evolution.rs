@@ -0,0 +1,315 @@
+use bevy::{
+    hierarchy::DespawnRecursiveExt,
+    log::info,
+    prelude::{
+        Changed, Commands, Entity, Event, EventReader, EventWriter, Query, Res, ResMut, With,
+    },
+    time::Time,
+};
+use vecmath::{vec3_len, vec3_sub};
+
+use crate::game::{
+    components::{
+        common::{Mass, Name, PendingGeneration, Radius, Temperature, ID},
+        orbit::{OrbitalElements, Parent, AU},
+        planet::{
+            Albedo, Atmosphere, BodyType, BodyTypes, CPlanetClass, EnvironmentBundle,
+            EquilibriumTemperature, EscapeVelocity, SurfaceGravity, SurfaceTemperature, SystemId,
+            WaterFraction,
+        },
+        spatial::Position,
+        star::{
+            Age, CEvolutionStage, CStarClass, CStarType, EvolutionStage, FlareActivity, FrostLine,
+            HabitableZone, Lifetime, Luminosity, Metallicity, SpectralSubclass, Star, StellarState,
+            Variability, GIANT_PHASE,
+        },
+    },
+    resourses::{evolution::EvolutionConfig, galaxy::GalaxyConfig, time::GameTime},
+};
+
+/// Fired when a star or companion moves to its next evolutionary stage.
+#[derive(Event)]
+pub struct StarEvolved {
+    pub star: ID,
+    /// Primary of the system the star belongs to.
+    pub system: ID,
+    /// New luminosity over the old one.
+    pub luminosity_ratio: f32,
+}
+
+/// Fired when a massive star explodes on its way to becoming a remnant.
+#[derive(Event)]
+pub struct Supernova {
+    pub star: ID,
+    /// Primary of the system the star belongs to.
+    pub system: ID,
+}
+
+pub fn advance_game_time(time: Res<Time>, mut game_time: ResMut<GameTime>) {
+    game_time.delta = time.delta_secs_f64() * game_time.speed;
+    game_time.years += game_time.delta;
+}
+
+/// Everything that changes as a star evolves.
+type EvolvingStar = (
+    &'static ID,
+    Option<&'static SystemId>,
+    &'static mut Age,
+    &'static mut Lifetime,
+    &'static mut CEvolutionStage,
+    &'static mut CStarType,
+    &'static mut SpectralSubclass,
+    &'static mut CStarClass,
+    &'static mut Mass,
+    &'static mut Luminosity,
+    &'static mut Radius,
+    &'static mut Temperature,
+    &'static mut HabitableZone,
+    &'static mut FrostLine,
+    (&'static mut Variability, &'static mut FlareActivity),
+);
+
+/// Ages every star and moves it from the main sequence to a giant once its
+/// lifetime is spent, then to a remnant once the giant phase is over.
+pub fn evolve_stars(
+    game_time: Res<GameTime>,
+    galaxy: Res<GalaxyConfig>,
+    config: Res<EvolutionConfig>,
+    mut stars: Query<EvolvingStar>,
+    mut evolved: EventWriter<StarEvolved>,
+    mut supernovae: EventWriter<Supernova>,
+) {
+    if game_time.delta <= 0.0 {
+        return;
+    }
+
+    for (
+        id,
+        system,
+        mut age,
+        mut lifetime,
+        mut stage,
+        mut star_type,
+        mut subclass,
+        mut star_class,
+        mut mass,
+        mut luminosity,
+        mut radius,
+        mut temperature,
+        mut habitable_zone,
+        mut frost_line,
+        (mut variability, mut flares),
+    ) in stars.iter_mut()
+    {
+        age.0 += game_time.delta;
+
+        let system = system.map_or(id, |s| &s.0);
+        let state = match stage.0 {
+            EvolutionStage::MainSequence if age.0 > lifetime.0 => {
+                stage.0 = EvolutionStage::Giant;
+                Star::giant(mass.0, config.supernova_mass)
+            }
+            EvolutionStage::Giant if age.0 > lifetime.0 * (1.0 + GIANT_PHASE) => {
+                if mass.0 >= config.supernova_mass {
+                    supernovae.send(Supernova {
+                        star: id.clone(),
+                        system: system.clone(),
+                    });
+                }
+                stage.0 = EvolutionStage::Remnant;
+                lifetime.0 = f64::INFINITY;
+                Star::remnant(mass.0, config.supernova_mass, config.black_hole_mass)
+            }
+            _ => continue,
+        };
+
+        let StellarState {
+            star_type: new_type,
+            subclass: new_subclass,
+            star_class: new_class,
+            mass: new_mass,
+            luminosity: new_luminosity,
+            radius: new_radius,
+            temperature: new_temperature,
+        } = state;
+        let luminosity_ratio = new_luminosity / luminosity.0;
+        star_type.0 = new_type;
+        subclass.0 = new_subclass;
+        star_class.0 = new_class;
+        mass.0 = new_mass;
+        luminosity.0 = new_luminosity;
+        radius.0 = new_radius;
+        temperature.0 = new_temperature;
+        *habitable_zone = HabitableZone::around(new_luminosity);
+        *frost_line = FrostLine::around(new_luminosity);
+        let mut rng = galaxy.event_rng(id, stage.0 as u64);
+        (*variability, *flares) = Star::get_variability(&mut rng, new_type, new_class);
+
+        evolved.send(StarEvolved {
+            star: id.clone(),
+            system: system.clone(),
+            luminosity_ratio,
+        });
+    }
+}
+
+/// Warms or cools the bodies lit by an evolved star. Bodies on wide orbits
+/// around a whole group of stars are treated as lit by the primary alone.
+pub fn rescale_equilibrium_temperatures(
+    mut evolved: EventReader<StarEvolved>,
+    mut bodies: Query<(&ID, &SystemId, &Parent, &mut EquilibriumTemperature)>,
+) {
+    for event in evolved.read() {
+        let factor = event.luminosity_ratio.powf(0.25);
+        if !factor.is_finite() {
+            continue;
+        }
+        let host = |parent: &Parent| parent.0.as_ref().unwrap_or(&event.system).clone();
+        let lit = bodies
+            .iter()
+            .filter(|(_, system, parent, _)| system.0 == event.system && host(parent) == event.star)
+            .map(|(id, ..)| id.clone())
+            .collect::<Vec<_>>();
+        for (id, _, parent, mut temperature) in bodies.iter_mut() {
+            let moon = parent.0.as_ref().is_some_and(|parent| lit.contains(parent));
+            if lit.contains(id) || moon {
+                temperature.0 *= factor;
+            }
+        }
+    }
+}
+
+/// What a body's environment is derived from, and the environment itself.
+type BodyEnvironment = (
+    &'static Mass,
+    &'static Radius,
+    &'static WaterFraction,
+    &'static EquilibriumTemperature,
+    &'static mut CPlanetClass,
+    &'static mut SurfaceGravity,
+    &'static mut EscapeVelocity,
+    &'static mut Atmosphere,
+    &'static mut Albedo,
+    &'static mut SurfaceTemperature,
+);
+
+/// Re-derives the environment of bodies whose starlight changed.
+pub fn update_environments(mut bodies: Query<BodyEnvironment, Changed<EquilibriumTemperature>>) {
+    for (
+        mass,
+        radius,
+        water,
+        temperature,
+        mut class,
+        mut gravity,
+        mut escape_velocity,
+        mut atmosphere,
+        mut albedo,
+        mut surface_temperature,
+    ) in bodies.iter_mut()
+    {
+        let environment = EnvironmentBundle::derive(mass.0, radius.0, water.0, temperature.0);
+        *class = environment.class;
+        *gravity = environment.gravity;
+        *escape_velocity = environment.escape_velocity;
+        *atmosphere = environment.atmosphere;
+        *albedo = environment.albedo;
+        *surface_temperature = environment.surface_temperature;
+    }
+}
+
+/// Bodies of the exploding star's system that its supernova destroys: the
+/// planets it hosts inside `radius` meters, with their moons and rings.
+/// `bodies` are each body's ID, parent, orbit and type.
+pub fn supernova_victims(
+    bodies: &[(&ID, &Parent, &OrbitalElements, BodyTypes)],
+    supernova: &Supernova,
+    radius: f64,
+) -> Vec<ID> {
+    let host = |parent: &Parent| parent.0.as_ref().unwrap_or(&supernova.system).clone();
+    let mut destroyed = bodies
+        .iter()
+        .filter(|(_, parent, orbit, body_type)| {
+            !matches!(body_type, BodyTypes::Star)
+                && host(parent) == supernova.star
+                && orbit.periapsis() < radius
+        })
+        .map(|(id, ..)| (*id).clone())
+        .collect::<Vec<_>>();
+    let moons = bodies
+        .iter()
+        .filter(|(_, parent, ..)| {
+            parent
+                .0
+                .as_ref()
+                .is_some_and(|parent| destroyed.contains(parent))
+        })
+        .map(|(id, ..)| (*id).clone())
+        .collect::<Vec<_>>();
+    destroyed.extend(moons);
+    destroyed
+}
+
+/// Destroys the exploding star's inner planets with their moons and
+/// enriches the neighbouring systems with its metals. Systems still without
+/// planets remember the explosion for when they get them.
+pub fn apply_supernovae(
+    mut commands: Commands,
+    mut supernovae: EventReader<Supernova>,
+    config: Res<EvolutionConfig>,
+    mut stars: Query<(&ID, &Name, &Position, &mut Metallicity), With<Star>>,
+    mut pending: Query<(&ID, &mut PendingGeneration)>,
+    bodies: Query<(Entity, &ID, &SystemId, &Parent, &OrbitalElements, &BodyType)>,
+) {
+    for supernova in supernovae.read() {
+        if let Some((_, mut pending)) = pending.iter_mut().find(|(id, _)| **id == supernova.system)
+        {
+            pending.exploded.push(supernova.star.clone());
+        }
+
+        let system = bodies
+            .iter()
+            .filter(|(_, _, system, ..)| system.0 == supernova.system)
+            .collect::<Vec<_>>();
+        let victims = supernova_victims(
+            &system
+                .iter()
+                .map(|(_, id, _, parent, orbit, body_type)| (*id, *parent, *orbit, body_type.0))
+                .collect::<Vec<_>>(),
+            supernova,
+            config.supernova_destruction_radius * AU,
+        );
+        let destroyed = system
+            .iter()
+            .filter(|(_, id, ..)| victims.contains(id))
+            .map(|(entity, ..)| *entity)
+            .collect::<Vec<_>>();
+        for entity in destroyed.iter() {
+            commands.entity(*entity).despawn_recursive();
+        }
+
+        let Some((name, origin)) = stars
+            .iter()
+            .find(|(id, ..)| **id == supernova.system)
+            .map(|(_, name, position, _)| (name.0.clone(), position.0))
+        else {
+            continue;
+        };
+        info!(
+            "Supernova in {}, {} bodies destroyed",
+            name,
+            destroyed.len()
+        );
+
+        for (id, _, position, mut metallicity) in stars.iter_mut() {
+            if *id == supernova.system {
+                continue;
+            }
+            let distance = vec3_len(vec3_sub(position.0, origin));
+            if distance < config.supernova_blast_radius {
+                metallicity.0 +=
+                    config.supernova_enrichment * (1.0 - distance / config.supernova_blast_radius);
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use bevy::{
     ecs::{
         entity::Entity,
         query::With,
-        system::{Commands, Query, Res},
+        system::{Commands, Query, Res, ResMut},
     },
     log::tracing_subscriber::filter,
 };
@@ -12,58 +12,109 @@ use egui::Vec2;
 
 use crate::game::{
     components::{
-        common::{Radius, ID},
-        orbit::{OrbitPeriod, OrbitPosition, OrbitRadius, Parent},
-        planet::{BodyType, SystemId},
-        system_map::CelestialBodyData,
+        common::{Mass, Radius, ID},
+        orbit::{OrbitPeriod, OrbitalElements, Parent},
+        planet::{
+            Albedo, Annulus, Atmosphere, BodyType, CPlanetClass, EnvironmentBundle,
+            EquilibriumTemperature, EscapeVelocity, HeavyElements, SurfaceGravity,
+            SurfaceTemperature, SystemId,
+        },
+        star::{FrostLine, HabitableZone, Star},
+        system_map::{CelestialBodyData, SystemMap},
     },
-    resourses::system::SelectedSystem,
+    resourses::{system::SelectedSystem, time::GameTime},
 };
 
+/// What the system map shows of a body.
+type MapBody = (
+    &'static SystemId,
+    &'static ID,
+    &'static Parent,
+    &'static OrbitalElements,
+    &'static OrbitPeriod,
+    &'static BodyType,
+    (
+        &'static Radius,
+        &'static Mass,
+        Option<&'static EquilibriumTemperature>,
+        Option<&'static Annulus>,
+        Option<&'static HeavyElements>,
+        Option<(
+            &'static CPlanetClass,
+            &'static SurfaceGravity,
+            &'static EscapeVelocity,
+            &'static Atmosphere,
+            &'static Albedo,
+            &'static SurfaceTemperature,
+        )>,
+    ),
+);
+
 pub fn update_system_map(
     system_id: Res<SelectedSystem>,
-    bodies: Query<(
-        &SystemId,
-        &ID,
-        &Parent,
-        &OrbitRadius,
-        &OrbitPosition,
-        &OrbitPeriod,
-        &BodyType,
-        &Radius,
-    )>,
+    game_time: Res<GameTime>,
+    bodies: Query<MapBody>,
+    stars: Query<(&ID, &HabitableZone, &FrostLine), With<Star>>,
     system_map_objecs: Query<Entity, With<CelestialBodyData>>,
+    mut map: ResMut<SystemMap>,
     mut commands: Commands,
 ) {
     system_map_objecs
         .iter()
         .for_each(|e| commands.entity(e).despawn());
 
+    let primary = stars
+        .iter()
+        .find(|(id, _, _)| system_id.0.as_ref() == Some(*id));
+    map.habitable_zone = primary.map(|(_, zone, _)| (zone.inner as f32, zone.outer as f32));
+    map.frost_line = primary.map(|(_, _, frost_line)| frost_line.0 as f32);
+
     if system_id.0.is_none() {
+        map.selected_body = None;
         return;
     }
 
-    let current_bodies: Vec<(_, _, _, _, _, _, _, _)> = bodies
+    // Top-down view of the reference plane.
+    let offset = |elements: &OrbitalElements, period: &OrbitPeriod| {
+        let [x, y, _] = elements.position_at(game_time.years, period.0);
+        Vec2::new(x as f32, y as f32)
+    };
+
+    let current_bodies: Vec<(_, _, _, _, _, _, _)> = bodies
         .iter()
-        .filter(|(s, _, _, _, _, _, _, _)| &s.0 == system_id.0.as_ref().unwrap())
+        .filter(|(s, _, _, _, _, _, _)| &s.0 == system_id.0.as_ref().unwrap())
         .collect();
 
+    if !current_bodies
+        .iter()
+        .any(|(_, id, ..)| map.selected_body.as_ref() == Some(*id))
+    {
+        map.selected_body = None;
+    }
+
     let orphans = current_bodies
         .iter()
-        .filter(|(_, _, p, _, _, _, _, _)| p.0.is_none());
+        .filter(|(_, _, p, _, _, _, _)| p.0.is_none());
 
     let mut to_spawn = Vec::<(ID, CelestialBodyData)>::with_capacity(current_bodies.len());
 
     let mut added_ids = Vec::<ID>::new();
-    orphans.for_each(|(_, id, _, o_r, o_p, _, b_t, r)| {
+    orphans.for_each(|(_, id, _, o_e, o_t, b_t, (r, m, t, a, h, env))| {
         to_spawn.push((
             ID(id.0.clone()),
             CelestialBodyData {
-                position: Vec2::new(o_p.0.cos() as f32, o_p.0.sin() as f32) * (o_r.0 as f32),
+                id: ID(id.0.clone()),
+                position: offset(o_e, o_t),
                 orbit_center: Vec2::ZERO,
-                orbit_radius: o_r.0 as f32,
+                elements: **o_e,
+                annulus: a.copied().unwrap_or_default(),
+                environment: environment(*env),
                 body_type: b_t.0,
                 radius: r.0,
+                mass: m.0,
+                orbit_period: o_t.0,
+                equilibrium_temperature: t.map(|t| t.0),
+                heavy_elements: h.map(|h| h.0),
             },
         ));
         added_ids.push(ID(id.0.clone()));
@@ -73,15 +124,15 @@ pub fn update_system_map(
         added_ids = to_spawn.iter().map(|(id, _)| id.clone()).collect();
         current_bodies
             .iter()
-            .filter(|(_, _, p, _, _, _, _, _)| p.0.is_some())
-            .filter(|(_, id, _, _, _, _, _, _)| added_ids.iter().all(|added_id| added_id != *id))
-            .filter(|(_, _, p, _, _, _, _, _)| {
+            .filter(|(_, _, p, _, _, _, _)| p.0.is_some())
+            .filter(|(_, id, _, _, _, _, _)| added_ids.iter().all(|added_id| added_id != *id))
+            .filter(|(_, _, p, _, _, _, _)| {
                 added_ids
                     .iter()
                     .any(|host_id| p.0.clone().unwrap() == host_id.clone())
             })
-            .for_each(|(_, id, p, o_r, o_p, _, b_t, r)| {
-                let mut pos = Vec2::new(o_p.0.cos() as f32, o_p.0.sin() as f32) * (o_r.0 as f32);
+            .for_each(|(_, id, p, o_e, o_t, b_t, (r, m, t, a, h, env))| {
+                let mut pos = offset(o_e, o_t);
                 let probably_parent = to_spawn
                     .iter()
                     .find(|(parent_id, _)| p.0.clone().unwrap() == parent_id.clone());
@@ -95,11 +146,18 @@ pub fn update_system_map(
                 to_spawn.push((
                     ID(id.0.clone()),
                     CelestialBodyData {
+                        id: ID(id.0.clone()),
                         position: pos,
                         orbit_center,
-                        orbit_radius: o_r.0 as f32,
+                        elements: **o_e,
+                        annulus: a.copied().unwrap_or_default(),
+                        environment: environment(*env),
                         body_type: b_t.0,
                         radius: r.0,
+                        mass: m.0,
+                        orbit_period: o_t.0,
+                        equilibrium_temperature: t.map(|t| t.0),
+                        heavy_elements: h.map(|h| h.0),
                     },
                 ));
             });
@@ -109,3 +167,24 @@ pub fn update_system_map(
         commands.spawn(d);
     });
 }
+
+fn environment(
+    components: Option<(
+        &CPlanetClass,
+        &SurfaceGravity,
+        &EscapeVelocity,
+        &Atmosphere,
+        &Albedo,
+        &SurfaceTemperature,
+    )>,
+) -> Option<EnvironmentBundle> {
+    let (class, gravity, escape_velocity, atmosphere, albedo, surface_temperature) = components?;
+    Some(EnvironmentBundle {
+        class: *class,
+        gravity: *gravity,
+        escape_velocity: *escape_velocity,
+        atmosphere: *atmosphere,
+        albedo: *albedo,
+        surface_temperature: *surface_temperature,
+    })
+}
@@ -4,7 +4,7 @@ use crate::{
             galaxy_map::GalaxyMap,
             system_map::{CelestialBodyData, SystemMap},
         },
-        resourses::{galaxy::GalaxyConfig, system::SelectedSystem},
+        resourses::{galaxy::GalaxyConfig, hyperlanes::HyperlaneGraph, system::SelectedSystem},
     },
     ui::galaxy_map::{render_galaxy_map, Interaction},
 };
@@ -17,7 +17,8 @@ use egui::{Frame, Ui};
 pub fn draw_galaxy_map(
     mut ui: &mut Ui,
     map: ResMut<GalaxyMap>,
-    config: Res<GalaxyConfig>,
+    config: &GalaxyConfig,
+    lanes: Res<HyperlaneGraph>,
     mut selected_system: ResMut<SelectedSystem>,
     mut system_map: ResMut<SystemMap>,
     celestial_bodies: Query<&CelestialBodyData>,
@@ -31,6 +32,7 @@ pub fn draw_galaxy_map(
                 system_map,
                 celestial_bodies,
                 config,
+                lanes,
                 selected_system,
             );
         });
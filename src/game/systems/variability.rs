@@ -0,0 +1,115 @@
+use bevy::prelude::{Commands, Entity, Event, EventReader, EventWriter, Query, Res, With};
+use rand::Rng;
+use rand_distr::Exp1;
+
+use crate::game::{
+    components::{
+        common::ID,
+        orbit::{OrbitalElements, Parent, AU},
+        planet::SystemId,
+        star::{ActiveFlare, FlareActivity, LuminosityModifier, Variability},
+        structure::{Exposed, Integrity},
+    },
+    resourses::{galaxy::GalaxyConfig, time::GameTime, variability::VariabilityConfig},
+};
+
+/// Fired when a star starts to flare.
+#[derive(Event)]
+pub struct StellarFlare {
+    pub star: ID,
+    /// Primary of the system the star belongs to.
+    pub system: ID,
+    /// Fractional rise of luminosity.
+    pub boost: f32,
+}
+
+/// A star that may flare, with the flare it is going through.
+type FlaringStar = (
+    Entity,
+    &'static ID,
+    Option<&'static SystemId>,
+    &'static FlareActivity,
+    Option<&'static ActiveFlare>,
+);
+
+/// Decides for each step of game time, one flare duration long, whether a
+/// star flares through it, so flares do not depend on the frame rate or on
+/// the time speed. Flare sizes follow an exponential spread around the
+/// star's typical strength.
+pub fn trigger_flares(
+    mut commands: Commands,
+    game_time: Res<GameTime>,
+    galaxy: Res<GalaxyConfig>,
+    config: Res<VariabilityConfig>,
+    stars: Query<FlaringStar>,
+    mut flares: EventWriter<StellarFlare>,
+) {
+    if game_time.delta <= 0.0 {
+        return;
+    }
+
+    let step = (game_time.years / config.flare_duration) as u64;
+    for (entity, id, system, activity, active) in stars.iter() {
+        if active.is_some_and(|flare| flare.step == step) {
+            continue;
+        }
+        let mut rng = galaxy.event_rng(id, step);
+        let chance = 1.0 - (-activity.rate as f64 * config.flare_duration).exp();
+        if activity.rate <= 0.0 || !rng.random_bool(chance) {
+            if active.is_some() {
+                commands.entity(entity).remove::<ActiveFlare>();
+            }
+            continue;
+        }
+
+        let boost = activity.strength * rng.sample::<f32, _>(Exp1);
+        commands.entity(entity).insert(ActiveFlare { boost, step });
+        flares.send(StellarFlare {
+            star: id.clone(),
+            system: system.map_or(id, |s| &s.0).clone(),
+            boost,
+        });
+    }
+}
+
+/// Applies variability and any flare in progress on top of `Luminosity`.
+pub fn update_luminosity_modifiers(
+    game_time: Res<GameTime>,
+    mut stars: Query<(&Variability, Option<&ActiveFlare>, &mut LuminosityModifier)>,
+) {
+    for (variability, flare, mut modifier) in stars.iter_mut() {
+        let boost = flare.map_or(0.0, |flare| flare.boost);
+        modifier.0 = variability.factor(game_time.years) * (1.0 + boost);
+    }
+}
+
+/// Wears down the exposed structures lit by a flaring star, the closer to
+/// it the harder. Structures around a planet share the planet's distance.
+pub fn damage_exposed_structures(
+    mut flares: EventReader<StellarFlare>,
+    config: Res<VariabilityConfig>,
+    bodies: Query<(&ID, &Parent, &OrbitalElements)>,
+    mut structures: Query<(&SystemId, &Parent, &OrbitalElements, &mut Integrity), With<Exposed>>,
+) {
+    for flare in flares.read() {
+        let host = |parent: &Parent| parent.0.as_ref().unwrap_or(&flare.system).clone();
+        for (system, parent, orbit, mut integrity) in structures.iter_mut() {
+            if system.0 != flare.system {
+                continue;
+            }
+            let distance = if host(parent) == flare.star {
+                orbit.semi_major_axis
+            } else {
+                let Some((_, _, planet)) = bodies.iter().find(|(id, body_parent, _)| {
+                    Some(*id) == parent.0.as_ref() && host(body_parent) == flare.star
+                }) else {
+                    continue;
+                };
+                planet.semi_major_axis
+            };
+            let distance = (distance / AU).max(0.01) as f32;
+            let damage = config.flare_damage * flare.boost / distance.powi(2);
+            integrity.0 = (integrity.0 - damage).max(0.0);
+        }
+    }
+}
@@ -1,22 +1,34 @@
-use crate::game::components::star::{StarClass, StarType};
+use crate::game::components::cluster::ClusterType;
+use crate::game::components::nebula::NebulaType;
+use crate::game::components::star::{SpectralDesignation, StarClass, StarType};
 use crate::game::components::system_map::{CelestialBodyData, SystemMap};
+use crate::game::resourses::hyperlanes::HyperlaneGraph;
 use crate::game::resourses::system::SelectedSystem;
-use crate::game::{components::galaxy_map::GalaxyMap, resourses::galaxy::GalaxyConfig};
+use crate::game::{
+    components::galaxy_map::{GalaxyMap, MapProjection, StarData},
+    resourses::galaxy::GalaxyConfig,
+};
 use bevy::ecs::system::Query;
 use bevy::log::tracing_subscriber::fmt::format;
 use bevy::prelude::{Res, ResMut};
 use egui::emath::RectTransform;
 use egui::{
-    pos2, show_tooltip, vec2, Align2, Color32, FontId, Label, Pos2, Rect, Sense, Stroke,
-    TextWrapMode, Ui, Vec2,
+    show_tooltip, vec2, Align2, Color32, FontId, Label, Painter, Pos2, Rect, ScrollArea, Sense,
+    Shape, Slider, Stroke, TextWrapMode, Ui,
 };
+use vecmath::Vector3;
 
+use super::format_years;
 use super::system_map::render_system_map;
 
+/// Zoom below which sector borders and names fade in.
+const SECTOR_ZOOM: f32 = 0.3;
+
 #[derive(PartialEq)]
 pub enum Interaction {
     None,
     GoToSystemMap,
+    GoToGalaxyMap,
 }
 
 pub fn render_galaxy_map(
@@ -24,13 +36,13 @@ pub fn render_galaxy_map(
     mut map: ResMut<GalaxyMap>,
     mut system_map: ResMut<SystemMap>,
     celestial_bodies: Query<&CelestialBodyData>,
-    config: Res<GalaxyConfig>,
+    config: &GalaxyConfig,
+    lanes: Res<HyperlaneGraph>,
     mut selected_system: ResMut<SelectedSystem>,
 ) -> Interaction {
     let mut result = Interaction::None;
     let (response, painter) =
         ui.allocate_painter(ui.available_size_before_wrap(), Sense::click_and_drag());
-    let stars = map.stars.lock().unwrap();
 
     let to_screen = RectTransform::from_to(
         Rect::from_x_y_ranges(
@@ -41,41 +53,173 @@ pub fn render_galaxy_map(
         response.rect,
     );
 
+    let projection = map.projection;
+    if let Some(focus) = map.focus.take() {
+        map.position = response.rect.center() - to_screen.transform_pos(projection.project(focus));
+    }
+
+    let offset = map.position;
+    let to_pos =
+        |position: Vector3<f32>| to_screen.transform_pos(projection.project(position)) + offset;
+
+    let stars = map.stars.lock().unwrap();
+
     painter.rect_filled(response.rect, 0.0, Color32::from_rgb(12, 12, 36));
 
+    // The bulge is an ellipsoid, its outline squashes less than the disk's.
+    let bulge_radius = config.bulge_radius * config.galaxy_radius * to_screen.scale().x;
+    let flattening = config.bulge_scale_height / config.bulge_radius.max(f32::EPSILON);
+    let bulge_squash = projection
+        .project([0.0, 1.0, 0.0])
+        .y
+        .hypot(projection.project([0.0, 0.0, flattening]).y);
+    let core_pos = to_pos([0.0, 0.0, 0.0]);
+    for step in (1..=4).filter(|_| map.core.is_some()) {
+        let radius = bulge_radius * step as f32 / 2.0;
+        painter.add(Shape::ellipse_filled(
+            core_pos,
+            vec2(radius, radius * bulge_squash),
+            Color32::from_rgba_unmultiplied(255, 210, 150, 10),
+        ));
+    }
+
+    for nebula in map.nebulae.iter() {
+        let color = match nebula.nebula_type {
+            NebulaType::Emission => Color32::from_rgba_unmultiplied(230, 80, 120, 12),
+            NebulaType::Dark => Color32::from_rgba_unmultiplied(0, 0, 8, 40),
+            NebulaType::Reflection => Color32::from_rgba_unmultiplied(90, 140, 255, 12),
+        };
+        for (center, radius) in nebula.shape.puffs.iter() {
+            painter.circle_filled(to_pos(*center), radius * to_screen.scale().x, color);
+        }
+    }
+
+    for cluster in map.clusters.iter() {
+        let color = match cluster.cluster_type {
+            ClusterType::Open => Color32::from_rgba_unmultiplied(150, 190, 255, 60),
+            ClusterType::Globular => Color32::from_rgba_unmultiplied(255, 210, 120, 60),
+        };
+        let radius = 2.0 * cluster.radius * to_screen.scale().x;
+        painter.circle_stroke(to_pos(cluster.center), radius, Stroke::new(1.0, color));
+        painter.text(
+            to_pos(cluster.center) - vec2(0.0, radius + 2.0),
+            Align2::CENTER_BOTTOM,
+            &cluster.name,
+            FontId::proportional(11.0),
+            color,
+        );
+    }
+
+    if map.zoom < SECTOR_ZOOM {
+        let alpha = 1.0 - map.zoom / SECTOR_ZOOM;
+        let border = Stroke::new(
+            1.0,
+            Color32::from_rgba_unmultiplied(120, 200, 160, (alpha * 90.0) as u8),
+        );
+        let label = Color32::from_rgba_unmultiplied(160, 230, 190, (alpha * 200.0) as u8);
+        let to_polar = |r: f32, angle: f32| to_pos([r * angle.cos(), r * angle.sin(), 0.0]);
+
+        for sector in map.sectors.iter() {
+            if sector.ring > 0 {
+                painter.line_segment(
+                    [
+                        to_polar(sector.inner_radius, sector.start_angle),
+                        to_polar(sector.outer_radius, sector.start_angle),
+                    ],
+                    border,
+                );
+            }
+
+            let steps = 24;
+            let arc = (0..=steps)
+                .map(|i| {
+                    let t = i as f32 / steps as f32;
+                    let angle = sector.start_angle + t * (sector.end_angle - sector.start_angle);
+                    to_polar(sector.outer_radius, angle)
+                })
+                .collect();
+            painter.add(Shape::line(arc, border));
+
+            let center = sector.label_position();
+            painter.text(
+                to_pos([center[0], center[1], 0.0]),
+                Align2::CENTER_CENTER,
+                &sector.name,
+                FontId::proportional(12.0),
+                label,
+            );
+        }
+    }
+
+    let lane_stroke = Stroke::new(1.0, Color32::from_rgba_unmultiplied(90, 110, 170, 90));
+    for (from, to) in lanes.lanes() {
+        painter.line_segment([to_pos(from), to_pos(to)], lane_stroke);
+    }
+
+    // Right-clicking a star starts a route to whichever star is hovered.
+    let route = map
+        .route_origin
+        .as_ref()
+        .zip(map.hovered.map(|i| &stars[i].id))
+        .and_then(|(from, to)| lanes.shortest_path(from, to));
+    if let Some((path, _)) = route.as_ref() {
+        let points = path
+            .iter()
+            .filter_map(|id| lanes.position(id))
+            .map(to_pos)
+            .collect();
+        painter.add(Shape::line(
+            points,
+            Stroke::new(3.0, Color32::from_rgb(120, 220, 255)),
+        ));
+    }
+
     for star in stars.iter() {
-        let pos = to_screen.transform_pos(pos2(star.position[0], star.position[1])) + map.position;
-        painter.circle_filled(
-            pos,
-            match star.star_class {
-                StarClass::O => 7.0,
-                StarClass::I => 6.0,
-                StarClass::II => 5.0,
-                StarClass::III => 4.0,
-                StarClass::IV => 3.0,
-                StarClass::V => 2.0,
-            },
-            (match star.star_type {
-                StarType::O => Color32::from_hex("#92b5ff"),
-                StarType::B => Color32::from_hex("#a2c0ff"),
-                StarType::A => Color32::from_hex("#d5e0ff"),
-                StarType::F => Color32::from_hex("#f9f5ff"),
-                StarType::G => Color32::from_hex("#ffede3"),
-                StarType::K => Color32::from_hex("#ffdab5"),
-                StarType::M => Color32::from_hex("#ffb56c"),
-            })
-            .unwrap(),
+        draw_star(&painter, to_pos(star.position), star);
+    }
+
+    if let Some(origin) = map.route_origin.as_ref().and_then(|id| lanes.position(id)) {
+        painter.circle_stroke(
+            to_pos(origin),
+            10.0,
+            Stroke::new(2.0, Color32::from_rgb(120, 220, 255)),
+        );
+    }
+
+    if let Some(core) = map.core.as_ref() {
+        let pos = to_pos(core.position);
+        painter.circle_filled(pos, 6.0, Color32::BLACK);
+        painter.circle_stroke(pos, 7.0, Stroke::new(2.0, Color32::from_rgb(255, 160, 60)));
+        painter.text(
+            pos + vec2(10.0, 0.0),
+            Align2::LEFT_CENTER,
+            &core.name,
+            FontId::proportional(12.0),
+            Color32::from_rgb(255, 200, 140),
+        );
+    }
+
+    if let Some(star) = stars
+        .iter()
+        .find(|s| selected_system.0.as_ref() == Some(&s.id))
+    {
+        painter.circle_stroke(
+            to_pos(star.position),
+            12.0,
+            Stroke::new(2.0, Color32::YELLOW),
         );
     }
 
     if let Some(star_index) = map.hovered {
         let star = stars[star_index].clone();
         selected_system.0 = Some(star.id.clone());
-        let pos = to_screen.transform_pos(pos2(star.position[0], star.position[1])) + map.position;
-        painter.circle_stroke(pos, 10.0, Stroke::new(4.0, Color32::RED));
+        painter.circle_stroke(to_pos(star.position), 10.0, Stroke::new(4.0, Color32::RED));
         show_tooltip(ui.ctx(), ui.layer_id(), "system_info_popup".into(), |ui| {
-            // ui.label(format!("\"Name\" star"));
-            ui.add(Label::new("\"Name\" star"));
+            ui.add(Label::new(star_title(&star)).wrap_mode(TextWrapMode::Extend));
+            ui.add(
+                Label::new(format!("Spectral type: {}", star.spectral()))
+                    .wrap_mode(TextWrapMode::Extend),
+            );
             // ui.label(format!("Spectral type: {}", star.star_type));
             ui.add(
                 Label::new(format!("Spectral class: {}", star.star_type))
@@ -86,6 +230,89 @@ pub fn render_galaxy_map(
                 Label::new(format!("Luminosity class: {}", star.star_class))
                     .wrap_mode(TextWrapMode::Extend),
             );
+            ui.add(
+                Label::new(format!("Metallicity: {:+.2} dex", star.metallicity))
+                    .wrap_mode(TextWrapMode::Extend),
+            );
+            ui.add(
+                Label::new(format!("Age: {}, {}", format_years(star.age), star.stage))
+                    .wrap_mode(TextWrapMode::Extend),
+            );
+            if star.variability.amplitude > 0.0 {
+                ui.add(
+                    Label::new(format!(
+                        "Variability: ±{:.1}% over {}",
+                        star.variability.amplitude * 100.0,
+                        format_years(star.variability.period)
+                    ))
+                    .wrap_mode(TextWrapMode::Extend),
+                );
+            }
+            if star.flares.rate > 0.0 {
+                ui.add(
+                    Label::new(format!(
+                        "Flares: {:.1} per year, typically +{:.1}%",
+                        star.flares.rate,
+                        star.flares.strength * 100.0
+                    ))
+                    .wrap_mode(TextWrapMode::Extend),
+                );
+            }
+            ui.add(
+                Label::new(format!(
+                    "Output: {:.1}%{}",
+                    star.output * 100.0,
+                    if star.flaring { ", flaring" } else { "" }
+                ))
+                .wrap_mode(TextWrapMode::Extend),
+            );
+            ui.add(Label::new(format!("Sector: {}", star.sector)).wrap_mode(TextWrapMode::Extend));
+            ui.add(
+                Label::new(format!("Height above plane: {:+.0}", star.position[2]))
+                    .wrap_mode(TextWrapMode::Extend),
+            );
+            for companion in star.companions.iter() {
+                ui.add(
+                    Label::new(format!(
+                        "Companion {}: {}, {:.2} AU",
+                        companion.name,
+                        companion.spectral(),
+                        companion.separation
+                    ))
+                    .wrap_mode(TextWrapMode::Extend),
+                );
+            }
+            if let Some(cluster) = star.cluster.as_ref() {
+                ui.add(
+                    Label::new(format!("Member of {}", cluster)).wrap_mode(TextWrapMode::Extend),
+                );
+            }
+            for nebula in map
+                .nebulae
+                .iter()
+                .filter(|n| n.shape.contains(star.position))
+            {
+                ui.add(
+                    Label::new(format!("Inside {} ({})", nebula.name, nebula.nebula_type))
+                        .wrap_mode(TextWrapMode::Extend),
+                );
+            }
+            ui.add(
+                Label::new(format!("Hyperlanes: {}", lanes.neighbours(&star.id).len()))
+                    .wrap_mode(TextWrapMode::Extend),
+            );
+            if let Some((path, length)) = route.as_ref() {
+                ui.add(
+                    Label::new(format!(
+                        "Route: {} jumps, {:.0} long",
+                        path.len() - 1,
+                        length
+                    ))
+                    .wrap_mode(TextWrapMode::Extend),
+                );
+            } else if map.route_origin.is_some() {
+                ui.add(Label::new("No route").wrap_mode(TextWrapMode::Extend));
+            }
             ui.allocate_ui(vec2(400.0, 300.0), |ui| {
                 render_system_map(ui, system_map, celestial_bodies)
             })
@@ -96,9 +323,7 @@ pub fn render_galaxy_map(
             response
                 .hover_pos()
                 .unwrap()
-                .distance(
-                    to_screen.transform_pos(Pos2::new(s.position[0], s.position[1])) + map.position,
-                )
+                .distance(to_pos(s.position))
                 .abs()
                 < 10.0
         }) {
@@ -110,8 +335,16 @@ pub fn render_galaxy_map(
         None
     };
 
+    let route_origin = response
+        .secondary_clicked()
+        .then(|| map.hovered.map(|i| stars[i].id.clone()));
+
     drop(stars);
 
+    if let Some(origin) = route_origin {
+        map.route_origin = origin;
+    }
+
     if response.dragged() {
         map.position += response.drag_delta();
     }
@@ -126,3 +359,124 @@ pub fn render_galaxy_map(
     map.hovered = hovered_index;
     result
 }
+
+fn draw_star(painter: &Painter, pos: Pos2, star: &StarData) {
+    match star.star_type {
+        StarType::WhiteDwarf => {
+            painter.circle_filled(pos, 1.5, Color32::WHITE);
+            painter.circle_stroke(pos, 3.0, Stroke::new(1.0, Color32::from_rgb(200, 220, 255)));
+        }
+        StarType::NeutronStar => {
+            let color = Color32::from_rgb(140, 230, 255);
+            painter.circle_filled(pos, 1.5, color);
+            for ray in [vec2(4.0, 0.0), vec2(0.0, 4.0)] {
+                painter.line_segment([pos - ray, pos + ray], Stroke::new(1.0, color));
+            }
+        }
+        StarType::Pulsar => {
+            let color = Color32::from_rgb(140, 230, 255);
+            painter.circle_filled(pos, 2.0, color);
+            let beam = vec2(5.0, -5.0);
+            painter.line_segment([pos - beam, pos + beam], Stroke::new(1.5, color));
+        }
+        StarType::BlackHole => {
+            painter.circle_filled(pos, 3.0, Color32::BLACK);
+            painter.circle_stroke(pos, 3.5, Stroke::new(1.5, Color32::from_rgb(200, 120, 255)));
+        }
+        _ => {
+            painter.circle_filled(
+                pos,
+                match star.star_class {
+                    StarClass::O => 7.0,
+                    StarClass::Ia => 6.5,
+                    StarClass::Ib => 6.0,
+                    StarClass::II => 5.0,
+                    StarClass::III => 4.0,
+                    StarClass::IV => 3.0,
+                    StarClass::V | StarClass::VII => 2.0,
+                },
+                star_color(star.star_type),
+            );
+        }
+    }
+}
+
+pub fn star_color(star_type: StarType) -> Color32 {
+    match star_type {
+        StarType::O => Color32::from_hex("#92b5ff").unwrap(),
+        StarType::B => Color32::from_hex("#a2c0ff").unwrap(),
+        StarType::A => Color32::from_hex("#d5e0ff").unwrap(),
+        StarType::F => Color32::from_hex("#f9f5ff").unwrap(),
+        StarType::G => Color32::from_hex("#ffede3").unwrap(),
+        StarType::K => Color32::from_hex("#ffdab5").unwrap(),
+        StarType::M => Color32::from_hex("#ffb56c").unwrap(),
+        StarType::WhiteDwarf => Color32::WHITE,
+        StarType::NeutronStar | StarType::Pulsar => Color32::from_rgb(140, 230, 255),
+        StarType::BlackHole => Color32::from_rgb(200, 120, 255),
+    }
+}
+
+/// Star name, followed by its catalogue designation when the two differ.
+pub fn star_title(star: &StarData) -> String {
+    if star.name == star.designation {
+        star.name.clone()
+    } else {
+        format!("{} ({})", star.name, star.designation)
+    }
+}
+
+pub fn render_projection_controls(ui: &mut Ui, map: &mut GalaxyMap) {
+    ui.heading("View");
+    ui.horizontal(|ui| {
+        if ui
+            .selectable_label(map.projection == MapProjection::TopDown, "Top-down")
+            .clicked()
+        {
+            map.projection = MapProjection::TopDown;
+        }
+        let tilted = matches!(map.projection, MapProjection::Tilted { .. });
+        if ui.selectable_label(tilted, "Tilted").clicked() && !tilted {
+            map.projection = MapProjection::Tilted {
+                angle: MapProjection::DEFAULT_TILT,
+            };
+        }
+    });
+    if let MapProjection::Tilted { angle } = &mut map.projection {
+        ui.add(Slider::new(angle, 0.0..=1.5).text("Tilt"));
+    }
+}
+
+pub fn render_star_search(ui: &mut Ui, map: &mut GalaxyMap, selected_system: &mut SelectedSystem) {
+    ui.heading("Search");
+    ui.text_edit_singleline(&mut map.search);
+
+    let query = map.search.trim().to_lowercase();
+    let spectral = map.search.trim().parse::<SpectralDesignation>().ok();
+    if query.is_empty() {
+        return;
+    }
+
+    let stars = map.stars.clone();
+    let stars = stars.lock().unwrap();
+    ScrollArea::vertical().show(ui, |ui| {
+        for star in stars
+            .iter()
+            .filter(|s| {
+                s.name.to_lowercase().contains(&query)
+                    || s.designation.to_lowercase().contains(&query)
+                    || s.sector.to_lowercase().contains(&query)
+                    || spectral.is_some_and(|spectral| spectral == s.spectral())
+                    || s.cluster
+                        .as_ref()
+                        .is_some_and(|c| c.to_lowercase().contains(&query))
+            })
+            .take(100)
+        {
+            let selected = selected_system.0.as_ref() == Some(&star.id);
+            if ui.selectable_label(selected, star_title(star)).clicked() {
+                selected_system.0 = Some(star.id.clone());
+                map.focus = Some(star.position);
+            }
+        }
+    });
+}
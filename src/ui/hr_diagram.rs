@@ -0,0 +1,200 @@
+use egui::{
+    emath::RectTransform, pos2, show_tooltip, Align2, Color32, FontId, Label, Pos2, Rect, Sense,
+    Stroke, TextWrapMode, Ui,
+};
+
+use crate::game::{
+    components::{
+        galaxy_map::{CompanionData, GalaxyMap, StarData},
+        star::{SpectralDesignation, StarClass, StarType},
+    },
+    resourses::system::SelectedSystem,
+};
+
+use super::galaxy_map::{star_color, star_title, Interaction};
+
+/// Temperatures marked along the bottom axis, in kelvins.
+const TEMPERATURE_TICKS: [f32; 7] = [2500.0, 3500.0, 5000.0, 7500.0, 10_000.0, 20_000.0, 40_000.0];
+
+/// Star or companion on the diagram, with the system it belongs to.
+struct Plotted<'a> {
+    system: &'a StarData,
+    companion: Option<&'a CompanionData>,
+}
+
+impl Plotted<'_> {
+    fn star_type(&self) -> StarType {
+        self.companion
+            .map_or(self.system.star_type, |c| c.star_type)
+    }
+
+    fn star_class(&self) -> StarClass {
+        self.companion
+            .map_or(self.system.star_class, |c| c.star_class)
+    }
+
+    fn temperature(&self) -> f32 {
+        self.companion
+            .map_or(self.system.temperature, |c| c.temperature)
+    }
+
+    fn luminosity(&self) -> f32 {
+        self.companion
+            .map_or(self.system.luminosity, |c| c.luminosity)
+    }
+
+    fn spectral(&self) -> SpectralDesignation {
+        self.companion
+            .map_or(self.system.spectral(), |c| c.spectral())
+    }
+
+    fn title(&self) -> String {
+        match self.companion {
+            Some(companion) => format!("{}, companion of {}", companion.name, self.system.name),
+            None => star_title(self.system),
+        }
+    }
+}
+
+pub fn render_class_filters(ui: &mut Ui, hidden_classes: &mut Vec<StarClass>) {
+    ui.heading("Luminosity classes");
+    for star_class in StarClass::ALL {
+        let mut shown = !hidden_classes.contains(&star_class);
+        if ui.checkbox(&mut shown, star_class.to_string()).changed() {
+            if shown {
+                hidden_classes.retain(|c| *c != star_class);
+            } else {
+                hidden_classes.push(star_class);
+            }
+        }
+    }
+}
+
+/// Every star and companion by log temperature, hottest on the left, against log
+/// luminosity. Clicking one selects its system and shows it on the galaxy
+/// map.
+pub fn render_hr_diagram(
+    ui: &mut Ui,
+    map: &mut GalaxyMap,
+    selected_system: &mut SelectedSystem,
+    hidden_classes: &[StarClass],
+) -> Interaction {
+    let mut result = Interaction::None;
+    let (response, painter) = ui.allocate_painter(ui.available_size_before_wrap(), Sense::click());
+    painter.rect_filled(response.rect, 0.0, Color32::from_rgb(12, 12, 36));
+
+    let stars = map.stars.clone();
+    let stars = stars.lock().unwrap();
+    // Black holes and the like have nothing to plot on log axes.
+    let points = stars
+        .iter()
+        .flat_map(|system| {
+            let companions = system.companions.iter().map(move |companion| Plotted {
+                system,
+                companion: Some(companion),
+            });
+            std::iter::once(Plotted {
+                system,
+                companion: None,
+            })
+            .chain(companions)
+        })
+        .filter(|s| !hidden_classes.contains(&s.star_class()))
+        .filter(|s| s.temperature() > 0.0 && s.luminosity() > 0.0)
+        .map(|s| {
+            let point = pos2(s.temperature().log10(), s.luminosity().log10());
+            (s, point)
+        })
+        .collect::<Vec<_>>();
+    if points.is_empty() {
+        return result;
+    }
+
+    let (mut min, mut max) = (points[0].1, points[0].1);
+    for (_, point) in points.iter() {
+        min = min.min(*point);
+        max = max.max(*point);
+    }
+    let plot = response.rect.shrink(40.0);
+    let to_screen = RectTransform::from_to(
+        Rect::from_x_y_ranges(
+            (max.x + 0.05)..=(min.x - 0.05),
+            (max.y + 0.5)..=(min.y - 0.5),
+        ),
+        plot,
+    );
+
+    let grid = Stroke::new(1.0, Color32::from_gray(50));
+    let font = FontId::proportional(12.0);
+    for decade in (min.y - 0.5).ceil() as i32..=(max.y + 0.5).floor() as i32 {
+        let y = to_screen.transform_pos(pos2(max.x, decade as f32)).y;
+        painter.hline(plot.x_range(), y, grid);
+        painter.text(
+            pos2(plot.left() - 4.0, y),
+            Align2::RIGHT_CENTER,
+            format!("1e{}", decade),
+            font.clone(),
+            Color32::GRAY,
+        );
+    }
+    for temperature in TEMPERATURE_TICKS {
+        let x = to_screen.transform_pos(pos2(temperature.log10(), max.y)).x;
+        if !plot.x_range().contains(x) {
+            continue;
+        }
+        painter.vline(x, plot.y_range(), grid);
+        painter.text(
+            pos2(x, plot.bottom() + 4.0),
+            Align2::CENTER_TOP,
+            format!("{} K", temperature),
+            font.clone(),
+            Color32::GRAY,
+        );
+    }
+    painter.text(
+        plot.left_top() - egui::vec2(0.0, 24.0),
+        Align2::LEFT_TOP,
+        "Luminosity (L☉)",
+        font.clone(),
+        Color32::GRAY,
+    );
+
+    let mut hovered = None;
+    let mut hovered_distance = 6.0;
+    for (star, point) in points.iter() {
+        let pos = to_screen.transform_pos(*point);
+        painter.circle_filled(pos, 2.0, star_color(star.star_type()));
+        if star.companion.is_none() && selected_system.0.as_ref() == Some(&star.system.id) {
+            painter.circle_stroke(pos, 6.0, Stroke::new(2.0, Color32::YELLOW));
+        }
+        if let Some(distance) = response.hover_pos().map(|p: Pos2| p.distance(pos)) {
+            if distance < hovered_distance {
+                hovered_distance = distance;
+                hovered = Some((star, pos));
+            }
+        }
+    }
+
+    if let Some((star, pos)) = hovered {
+        painter.circle_stroke(pos, 5.0, Stroke::new(2.0, Color32::RED));
+        show_tooltip(ui.ctx(), ui.layer_id(), "hr_star_popup".into(), |ui| {
+            ui.add(Label::new(star.title()).wrap_mode(TextWrapMode::Extend));
+            ui.add(
+                Label::new(format!(
+                    "{}, {:.0} K, {:.3} L☉",
+                    star.spectral(),
+                    star.temperature(),
+                    star.luminosity()
+                ))
+                .wrap_mode(TextWrapMode::Extend),
+            );
+        });
+        if response.clicked() {
+            selected_system.0 = Some(star.system.id.clone());
+            map.focus = Some(star.system.position);
+            result = Interaction::GoToGalaxyMap;
+        }
+    }
+
+    result
+}
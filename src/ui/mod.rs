@@ -1,15 +1,16 @@
 use crate::game::components::planet::CelestialBodyBundle;
-use crate::game::components::system_map::CelestialBodyData;
-use crate::game::components::{galaxy_map::GalaxyMap, system_map::SystemMap};
+use crate::game::components::star::StarClass;
 use crate::game::resourses::galaxy::GalaxyConfig;
-use crate::game::resourses::system::SelectedSystem;
-use crate::ui::screens::game_screen::draw_game_screen;
+use crate::game::resourses::generation::GalaxyGeneration;
+use crate::game::resourses::naming::NamingConfig;
+use crate::ui::screens::game_screen::{draw_game_screen, GameScreenParams};
+use crate::ui::screens::loading_screen::draw_loading_screen;
 use crate::ui::screens::main_menu::draw_main_menu;
-use bevy::ecs::system::Query;
 use bevy::prelude::{default, Res, ResMut, Resource};
 use bevy_egui::EguiContexts;
 
 pub mod galaxy_map;
+pub mod hr_diagram;
 pub mod screens;
 pub mod system_map;
 
@@ -17,15 +18,38 @@ pub mod system_map;
 pub enum Screen {
     #[default]
     MainMenu,
+    Loading,
     Game,
 }
 
 #[derive(Resource, Default, Clone, Copy)]
 pub struct CurrentScreen(pub Screen);
 
+/// Run condition for systems that only make sense with a galaxy on screen.
+pub fn in_game(screen: Res<CurrentScreen>) -> bool {
+    matches!(screen.0, Screen::Game)
+}
+
+/// Duration in the largest unit that keeps it above one.
+pub fn format_years(years: f64) -> String {
+    if years >= 1e9 {
+        format!("{:.2} Gyr", years / 1e9)
+    } else if years >= 1e6 {
+        format!("{:.2} Myr", years / 1e6)
+    } else if years >= 1e3 {
+        format!("{:.2} kyr", years / 1e3)
+    } else if years >= 1.0 {
+        format!("{:.0} yr", years)
+    } else {
+        format!("{:.0} d", years * 365.25)
+    }
+}
+
 #[derive(Default, Resource)]
 pub struct GameScreenFlags {
     pub current_tab: GameWindowTabs,
+    /// Luminosity classes left out of the HR diagram.
+    pub hidden_classes: Vec<StarClass>,
 }
 
 #[derive(Default)]
@@ -33,33 +57,32 @@ pub enum GameWindowTabs {
     #[default]
     GalaxyMap,
     SystemMap,
+    HrDiagram,
 }
 
 pub fn ui_system(
     mut contexts: EguiContexts,
     mut screen: ResMut<CurrentScreen>,
-    mut map: ResMut<GalaxyMap>,
-    mut system_map: ResMut<SystemMap>,
-    system_bodies: Query<&CelestialBodyData>,
-    mut game_screen_flags: ResMut<GameScreenFlags>,
-    mut selected_system: ResMut<SelectedSystem>,
-    config: Res<GalaxyConfig>,
+    game_screen: GameScreenParams,
+    mut config: ResMut<GalaxyConfig>,
+    mut generation: ResMut<GalaxyGeneration>,
+    naming: Res<NamingConfig>,
 ) {
     let msg: Message = match screen.0 {
-        Screen::MainMenu => draw_main_menu(contexts.ctx_mut()),
-        Screen::Game => draw_game_screen(
-            contexts.ctx_mut(),
-            map,
-            system_map,
-            system_bodies,
-            selected_system,
-            game_screen_flags,
-            config,
-        ),
+        Screen::MainMenu => draw_main_menu(contexts.ctx_mut(), &mut config),
+        Screen::Loading => draw_loading_screen(contexts.ctx_mut(), &generation.progress),
+        Screen::Game => draw_game_screen(contexts.ctx_mut(), game_screen, &config),
     };
 
     match msg {
-        Message::StartGame => screen.0 = Screen::Game,
+        Message::StartGame => {
+            generation.start(config.clone(), naming.clone());
+            screen.0 = Screen::Loading;
+        }
+        Message::CancelGeneration => {
+            generation.cancel();
+            screen.0 = Screen::MainMenu;
+        }
         _ => {}
     };
     // egui::Window::new("Hello").show(contexts.ctx_mut(), |ui| {
@@ -69,6 +92,7 @@ pub fn ui_system(
 
 pub enum Message {
     StartGame,
+    CancelGeneration,
 
     None,
 }
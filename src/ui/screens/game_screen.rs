@@ -1,24 +1,44 @@
 use crate::game::components::system_map::{CelestialBodyData, SystemMap};
 use crate::game::resourses::galaxy::GalaxyConfig;
+use crate::game::resourses::hyperlanes::HyperlaneGraph;
 use crate::game::resourses::system::SelectedSystem;
+use crate::game::resourses::time::GameTime;
 use crate::game::systems::ui::draw_galaxy_map;
-use crate::ui::galaxy_map::Interaction;
-use crate::ui::system_map::render_system_map;
-use crate::ui::{CurrentScreen, GameScreenFlags, GameWindowTabs, Screen};
+use crate::ui::galaxy_map::{render_projection_controls, render_star_search, Interaction};
+use crate::ui::hr_diagram::{render_class_filters, render_hr_diagram};
+use crate::ui::system_map::{render_body_inspector, render_system_map};
+use crate::ui::{format_years, CurrentScreen, GameScreenFlags, GameWindowTabs, Screen};
 use crate::{game::components::galaxy_map::GalaxyMap, ui::Message};
-use bevy::ecs::system::Query;
+use bevy::ecs::system::{Query, SystemParam};
 use bevy::prelude::{Res, ResMut};
 use egui::{Align, Layout};
 
+/// World state the game screen shows and edits.
+#[derive(SystemParam)]
+pub struct GameScreenParams<'w, 's> {
+    map: ResMut<'w, GalaxyMap>,
+    system_map: ResMut<'w, SystemMap>,
+    system_bodies: Query<'w, 's, &'static CelestialBodyData>,
+    selected_system: ResMut<'w, SelectedSystem>,
+    flags: ResMut<'w, GameScreenFlags>,
+    lanes: Res<'w, HyperlaneGraph>,
+    game_time: ResMut<'w, GameTime>,
+}
+
 pub fn draw_game_screen(
     ctx: &egui::Context,
-    mut map: ResMut<GalaxyMap>,
-    mut system_map: ResMut<SystemMap>,
-    system_bodies: Query<&CelestialBodyData>,
-    mut selected_system: ResMut<SelectedSystem>,
-    mut flags: ResMut<GameScreenFlags>,
-    config: Res<GalaxyConfig>,
+    params: GameScreenParams,
+    config: &GalaxyConfig,
 ) -> Message {
+    let GameScreenParams {
+        mut map,
+        system_map,
+        system_bodies,
+        mut selected_system,
+        mut flags,
+        lanes,
+        mut game_time,
+    } = params;
     let mut result = Message::None;
     egui::TopBottomPanel::top("tab_menu")
         .resizable(false)
@@ -31,14 +51,64 @@ pub fn draw_game_screen(
                 if ui.button("System Map").clicked() {
                     flags.current_tab = GameWindowTabs::SystemMap;
                 };
+
+                if ui.button("HR Diagram").clicked() {
+                    flags.current_tab = GameWindowTabs::HrDiagram;
+                };
+
+                ui.separator();
+                ui.label(format!("Elapsed: {}", format_years(game_time.years)));
+                for (label, speed) in GameTime::SPEEDS {
+                    if ui
+                        .selectable_label(game_time.speed == speed, label)
+                        .clicked()
+                    {
+                        game_time.speed = speed;
+                    }
+                }
             });
         });
     match flags.current_tab {
-        GameWindowTabs::GalaxyMap => {}
+        GameWindowTabs::GalaxyMap => {
+            egui::SidePanel::left("galaxy_search").show(ctx, |ui| {
+                render_projection_controls(ui, &mut map);
+                ui.separator();
+                render_star_search(ui, &mut map, &mut selected_system);
+            });
+        }
         GameWindowTabs::SystemMap => {
-            let _ = egui::SidePanel::left("ssss").show(ctx, |ui| {});
+            let _ = egui::SidePanel::left("ssss").show(ctx, |ui| {
+                let stars = map.stars.lock().unwrap();
+                if let Some(star) = stars
+                    .iter()
+                    .find(|s| selected_system.0.as_ref() == Some(&s.id))
+                {
+                    ui.heading(&star.name);
+                    ui.label(format!("Catalogue: {}", star.designation));
+                    ui.label(format!("Sector: {}", star.sector));
+                    ui.label(format!("Spectral type: {}", star.spectral()));
+                    ui.label(format!("Age: {}", format_years(star.age)));
+                    ui.label(format!("Stage: {}", star.stage));
+                    ui.label(format!("Spectral class: {}", star.star_type));
+                    ui.label(format!("Luminosity class: {}", star.star_class));
+                    for companion in star.companions.iter() {
+                        ui.separator();
+                        ui.label(&companion.name);
+                        ui.label(format!("Spectral type: {}", companion.spectral()));
+                        ui.label(format!("Spectral class: {}", companion.star_type));
+                        ui.label(format!("Luminosity class: {}", companion.star_class));
+                        ui.label(format!("Separation: {:.2} AU", companion.separation));
+                    }
+                }
+                ui.separator();
+                render_body_inspector(ui, &system_map, &system_bodies);
+            });
+        }
+        GameWindowTabs::HrDiagram => {
+            egui::SidePanel::left("hr_filters").show(ctx, |ui| {
+                render_class_filters(ui, &mut flags.hidden_classes);
+            });
         }
-        _ => {}
     }
     let mut interaction = Interaction::None;
     match flags.current_tab {
@@ -49,6 +119,7 @@ pub fn draw_game_screen(
                         ui,
                         map,
                         config,
+                        lanes,
                         selected_system,
                         system_map,
                         system_bodies,
@@ -63,11 +134,18 @@ pub fn draw_game_screen(
                 })
             });
         }
-        _ => {}
+        GameWindowTabs::HrDiagram => {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                interaction =
+                    render_hr_diagram(ui, &mut map, &mut selected_system, &flags.hidden_classes);
+            });
+        }
     }
 
-    if interaction == Interaction::GoToSystemMap {
-        flags.current_tab = GameWindowTabs::SystemMap;
+    match interaction {
+        Interaction::GoToSystemMap => flags.current_tab = GameWindowTabs::SystemMap,
+        Interaction::GoToGalaxyMap => flags.current_tab = GameWindowTabs::GalaxyMap,
+        Interaction::None => {}
     }
 
     return result;
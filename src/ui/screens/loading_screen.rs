@@ -0,0 +1,45 @@
+use std::sync::atomic::Ordering;
+
+use egui::{Button, ProgressBar, Vec2};
+
+use crate::game::resourses::generation::GenerationProgress;
+use crate::ui::Message;
+
+pub fn draw_loading_screen(ctx: &egui::Context, progress: &GenerationProgress) -> Message {
+    let mut result = Message::None;
+
+    let total = progress.total_stars.load(Ordering::Relaxed).max(1);
+    let placed = progress.stars_placed.load(Ordering::Relaxed);
+    let generated = progress.systems_generated.load(Ordering::Relaxed);
+    let linked = progress.stars_linked.load(Ordering::Relaxed);
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 3.0);
+            ui.set_max_width(400.0);
+            ui.heading("Generating galaxy");
+
+            ui.add(
+                ProgressBar::new(placed as f32 / total as f32)
+                    .text(format!("Stars placed: {} / {}", placed, total)),
+            );
+            ui.add(
+                ProgressBar::new(generated as f32 / total as f32)
+                    .text(format!("Systems generated: {} / {}", generated, total)),
+            );
+            ui.add(
+                ProgressBar::new(linked as f32 / total as f32)
+                    .text(format!("Hyperlanes: {} / {}", linked, total)),
+            );
+
+            ui.add_space(16.0);
+            if ui
+                .add_sized(Vec2::new(200.0, 40.0), Button::new("Cancel"))
+                .clicked()
+            {
+                result = Message::CancelGeneration;
+            }
+        });
+    });
+    result
+}
@@ -1,14 +1,65 @@
-use egui::{Button, Vec2};
+use egui::{Button, ComboBox, DragValue, Grid, Slider, Vec2};
 
+use crate::game::resourses::galaxy::{GalaxyConfig, GalaxyShape, LaneRule, StarCatalogue};
 use crate::ui::Message;
 
-pub fn draw_main_menu(ctx: &egui::Context) -> Message {
+pub fn draw_main_menu(ctx: &egui::Context, config: &mut GalaxyConfig) -> Message {
     let mut result = Message::None;
 
     egui::CentralPanel::default().show(ctx, |ui| {
-        ui.centered_and_justified(|ui| {
-            ui.set_max_size(Vec2::new(300.0, 50.0));
-            if ui.add(Button::new("Start example game")).clicked() {
+        ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 3.0);
+
+            Grid::new("galaxy_settings").num_columns(2).show(ui, |ui| {
+                ui.label("Seed");
+                ui.add(DragValue::new(&mut config.seed));
+                ui.end_row();
+
+                ui.label("Stars");
+                ui.add(Slider::new(&mut config.num_stars, 100..=100_000).logarithmic(true));
+                ui.end_row();
+
+                ui.label("Shape");
+                ComboBox::from_id_salt("galaxy_shape")
+                    .selected_text(config.shape.label())
+                    .show_ui(ui, |ui| {
+                        for shape in GalaxyShape::presets() {
+                            let selected = shape.label() == config.shape.label();
+                            if ui.selectable_label(selected, shape.label()).clicked() {
+                                config.shape = shape;
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Hyperlanes");
+                ComboBox::from_id_salt("lane_rule")
+                    .selected_text(config.lane_rule.label())
+                    .show_ui(ui, |ui| {
+                        for rule in [LaneRule::RelativeNeighbourhood, LaneRule::Proximity] {
+                            ui.selectable_value(&mut config.lane_rule, rule, rule.label());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("Max lane length");
+                ui.add(Slider::new(&mut config.max_lane_length, 50.0..=2000.0).logarithmic(true));
+                ui.end_row();
+            });
+
+            ui.add_space(16.0);
+            if ui
+                .add_sized(Vec2::new(300.0, 50.0), Button::new("Start example game"))
+                .clicked()
+            {
+                config.catalogue = None;
+                result = Message::StartGame;
+            };
+            if ui
+                .add_sized(Vec2::new(300.0, 50.0), Button::new("Local Neighbourhood"))
+                .clicked()
+            {
+                config.catalogue = Some(StarCatalogue::local_neighbourhood());
                 result = Message::StartGame;
             };
         });
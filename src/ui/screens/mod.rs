@@ -1,2 +1,3 @@
 pub mod game_screen;
+pub mod loading_screen;
 pub mod main_menu;
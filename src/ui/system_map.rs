@@ -1,25 +1,119 @@
+use std::f64::consts::TAU;
+
 use bevy::{
     color::Color,
     ecs::system::{Query, ResMut},
     log::{debug, warn},
 };
-use egui::{emath::RectTransform, vec2, Align2, Color32, Pos2, Rect, Sense, Stroke, Ui};
+use egui::{emath::RectTransform, vec2, Align2, Color32, Pos2, Rect, Sense, Shape, Stroke, Ui};
 
 use crate::game::components::{
-    planet::{BodyType, BodyTypes},
+    orbit::AU,
+    planet::{BodyType, BodyTypes, EARTH_MASS},
     system_map::{CelestialBodyData, SystemMap},
 };
 
+use super::format_years;
+
+/// Points per drawn orbit.
+const ORBIT_SEGMENTS: usize = 64;
+
+/// Meters in km when short of a hundredth of an AU, in AU otherwise.
+fn format_distance(meters: f64) -> String {
+    if meters < 0.01 * AU {
+        format!("{:.0} km", meters / 1000.0)
+    } else {
+        format!("{:.3} AU", meters / AU)
+    }
+}
+
+/// Details of the body selected on the system map.
+pub fn render_body_inspector(ui: &mut Ui, map: &SystemMap, bodies: &Query<&CelestialBodyData>) {
+    let Some(body) = bodies
+        .iter()
+        .find(|b| map.selected_body.as_ref() == Some(&b.id))
+    else {
+        return;
+    };
+    ui.heading(body.body_type.to_string());
+    match body.body_type {
+        BodyTypes::Star => ui.label(format!("Mass: {:.2} M☉", body.mass)),
+        _ if body.annulus.is_band() => ui.label(format!("Total mass: {:.2e} kg", body.mass)),
+        _ => ui.label(format!("Mass: {:.3} M⊕", body.mass / EARTH_MASS)),
+    };
+    if body.annulus.is_band() {
+        ui.label(format!(
+            "Inner edge: {}",
+            format_distance(body.annulus.inner)
+        ));
+        ui.label(format!(
+            "Outer edge: {}",
+            format_distance(body.annulus.outer)
+        ));
+    } else {
+        ui.label(format!(
+            "Semi-major axis: {}",
+            format_distance(body.elements.semi_major_axis)
+        ));
+        ui.label(format!("Eccentricity: {:.3}", body.elements.eccentricity));
+        ui.label(format!(
+            "Inclination: {:.1}°",
+            body.elements.inclination.to_degrees()
+        ));
+    }
+    ui.label(format!(
+        "Orbital period: {}",
+        format_years(body.orbit_period)
+    ));
+    if let Some(temperature) = body.equilibrium_temperature {
+        ui.label(format!("Equilibrium temperature: {:.0} K", temperature));
+    }
+    if let Some(deposits) = body.heavy_elements {
+        ui.label(format!("Heavy elements: {:.2}×", deposits));
+    }
+    let Some(environment) = body.environment.filter(|_| !body.annulus.is_band()) else {
+        return;
+    };
+    ui.separator();
+    ui.label(format!("Class: {}", environment.class.0));
+    ui.label(format!("Radius: {:.0} km", body.radius));
+    ui.label(format!("Surface gravity: {:.2} g", environment.gravity.0));
+    ui.label(format!(
+        "Escape velocity: {:.1} km/s",
+        environment.escape_velocity.0
+    ));
+    let atmosphere = environment.atmosphere;
+    ui.label(if atmosphere.pressure.is_infinite() {
+        "Atmosphere: deep hydrogen and helium envelope".to_string()
+    } else if atmosphere.pressure > 0.0 {
+        format!(
+            "Atmosphere: {:.3} bar, {}",
+            atmosphere.pressure, atmosphere.composition
+        )
+    } else {
+        "Atmosphere: none".to_string()
+    });
+    ui.label(format!("Albedo: {:.2}", environment.albedo.0));
+    if !atmosphere.pressure.is_infinite() {
+        ui.label(format!(
+            "Surface temperature: {:.0} K",
+            environment.surface_temperature.0
+        ));
+    }
+}
+
 pub fn render_system_map(
     ui: &mut Ui,
     mut map: ResMut<SystemMap>,
     bodies: Query<&CelestialBodyData>,
 ) {
-    let (response, painter) = ui.allocate_painter(ui.available_size_before_wrap(), Sense::drag());
+    let (response, painter) =
+        ui.allocate_painter(ui.available_size_before_wrap(), Sense::click_and_drag());
 
+    // Apoapses rather than positions, so the view holds still as bodies move.
     let furthest_orbit = bodies
         .iter()
-        .map(|b| (b.position.x.powi(2) + b.position.y.powi(2)).sqrt())
+        .map(|b| b.orbit_center.length() + b.elements.apoapsis().max(b.annulus.outer) as f32)
         .max_by(|a, b| a.total_cmp(b))
         .unwrap_or(10.0)
         * 1.62;
@@ -34,6 +128,35 @@ pub fn render_system_map(
 
     painter.rect_filled(response.rect, 0.0, Color32::from_rgb(12, 12, 36));
 
+    // Climate zones as bands: a stroke as wide as the band, along its middle.
+    let center = to_screen.transform_pos(Pos2::ZERO) + map.position;
+    let scale = to_screen.scale().x;
+    let shade = |inner: f32, outer: f32, color: Color32| {
+        painter.circle_stroke(
+            center,
+            (inner + outer) / 2.0 * scale,
+            Stroke::new((outer - inner) * scale, color),
+        );
+    };
+    if let Some(frost_line) = map.frost_line {
+        let edge =
+            frost_line.max(2.0 * furthest_orbit / map.zoom * (response.rect.aspect_ratio() + 1.0));
+        shade(
+            frost_line,
+            edge,
+            Color32::from_rgba_unmultiplied(60, 110, 200, 24),
+        );
+    }
+    if let Some((inner, outer)) = map.habitable_zone {
+        shade(
+            inner,
+            outer,
+            Color32::from_rgba_unmultiplied(40, 180, 70, 60),
+        );
+    }
+
+    let mut clicked = None;
+    let mut clicked_distance = 8.0;
     bodies.iter().for_each(|b| {
         let pos = to_screen.transform_pos(Pos2::new(b.position.x, b.position.y)) + map.position;
         let orbit_center =
@@ -45,16 +168,64 @@ pub fn render_system_map(
             BodyTypes::Planet => Color32::GREEN,
             BodyTypes::Moon => Color32::PURPLE,
             BodyTypes::GasGiant => Color32::RED,
+            BodyTypes::Star => Color32::GOLD,
         };
 
-        painter.circle_stroke(
-            orbit_center,
-            b.orbit_radius * to_screen.scale().x,
-            Stroke::new(1.0, Color32::WHITE),
-        );
+        if b.annulus.is_band() {
+            let inner = b.annulus.inner as f32 * scale;
+            let outer = b.annulus.outer as f32 * scale;
+            // Wide enough to see even when the band is thinner than a pixel.
+            let width = (outer - inner).max(3.0);
+            painter.circle_stroke(
+                orbit_center,
+                (inner + outer) / 2.0,
+                Stroke::new(width, color.gamma_multiply(0.5)),
+            );
+            if map.selected_body.as_ref() == Some(&b.id) {
+                let outline = Stroke::new(1.0, Color32::YELLOW);
+                painter.circle_stroke(orbit_center, (inner + outer - width) / 2.0, outline);
+                painter.circle_stroke(orbit_center, (inner + outer + width) / 2.0, outline);
+            }
+
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let from_middle = (pointer.distance(orbit_center) - (inner + outer) / 2.0).abs();
+                let distance = (from_middle - width / 2.0).max(0.0);
+                if distance < clicked_distance {
+                    clicked_distance = distance;
+                    clicked = Some(b.id.clone());
+                }
+            }
+            return;
+        }
+
+        // Top-down view of the orbit, as in `update_system_map`.
+        let orbit = (0..ORBIT_SEGMENTS)
+            .map(|i| {
+                let [x, y, _] = b.elements.point_at(TAU * i as f64 / ORBIT_SEGMENTS as f64);
+                orbit_center + to_screen.scale() * vec2(x as f32, y as f32)
+            })
+            .collect();
+        painter.add(Shape::closed_line(orbit, Stroke::new(1.0, Color32::WHITE)));
 
-        painter.circle_filled(pos, 5.0, color);
+        let size = match b.body_type {
+            BodyTypes::Star => 8.0,
+            _ => 5.0,
+        };
+        painter.circle_filled(pos, size, color);
+        if map.selected_body.as_ref() == Some(&b.id) {
+            painter.circle_stroke(pos, size + 3.0, Stroke::new(2.0, Color32::YELLOW));
+        }
+
+        if let Some(distance) = response.interact_pointer_pos().map(|p| p.distance(pos)) {
+            if distance < clicked_distance {
+                clicked_distance = distance;
+                clicked = Some(b.id.clone());
+            }
+        }
     });
+    if response.clicked() {
+        map.selected_body = clicked;
+    }
     if response.dragged() {
         map.position += response.drag_delta();
     }